@@ -1,5 +1,28 @@
 use clap::{Parser, Subcommand};
-use foojay_disco::{PackageQueryOptions, MajorVersionsQueryOptions};
+use std::io::Write;
+use foojay_disco::{
+    discover_jdks,
+    Architecture,
+    ArchiveType,
+    AuditReport,
+    AuditTarget,
+    Bitness,
+    DecodeWarning,
+    DiscoClient,
+    Fpu,
+    JdkStore,
+    LibCType,
+    Latest,
+    MajorVersionQuery,
+    MajorVersionsQueryOptions,
+    OperatingSystem,
+    PackageList,
+    PackageQueryOptions,
+    PackageType,
+    ReleaseStatus,
+    TermOfSupport,
+    Verification,
+};
 
 const FOOJAY_URL_VAR: &str = "FOOJAY_DISCO_API_URL";
 
@@ -10,37 +33,116 @@ struct Args {
     command: Command,
 }
 
+#[derive(Debug, clap::Args)]
+struct PackageFilters {
+    #[arg(long)]
+    version: Option<String>,
+    #[arg(long)]
+    jdk_version: Option<u32>,
+    /// May be repeated
+    #[arg(long)]
+    distribution: Vec<String>,
+    /// May be repeated
+    #[arg(long)]
+    architecture: Vec<Architecture>,
+    /// May be repeated
+    #[arg(long)]
+    fpu: Vec<Fpu>,
+    /// May be repeated
+    #[arg(long)]
+    archive_type: Vec<ArchiveType>,
+    /// May be repeated
+    #[arg(long)]
+    package_type: Vec<PackageType>,
+    /// May be repeated
+    #[arg(long)]
+    operating_system: Vec<OperatingSystem>,
+    /// May be repeated
+    #[arg(long)]
+    libc_type: Vec<LibCType>,
+    /// May be repeated
+    #[arg(long)]
+    release_status: Vec<ReleaseStatus>,
+    /// May be repeated
+    #[arg(long)]
+    term_of_support: Vec<TermOfSupport>,
+    /// May be repeated
+    #[arg(long)]
+    bitness: Vec<Bitness>,
+    #[arg(long)]
+    javafx_bundled: Option<bool>,
+    #[arg(long)]
+    directly_downloadable: Option<bool>,
+    #[arg(long)]
+    signature_available: Option<bool>,
+    #[arg(long)]
+    free_to_use_in_production: Option<bool>,
+    #[arg(long)]
+    tck_tested: Option<Verification>,
+    #[arg(long)]
+    aqavit_certified: Option<Verification>,
+    /// May be repeated
+    #[arg(long)]
+    feature: Vec<String>,
+    /// May be repeated
+    #[arg(long)]
+    discovery_scope_id: Vec<String>,
+    #[arg(long)]
+    include_versions: Option<bool>,
+    #[arg(long)]
+    latest: Option<Latest>,
+}
+
+impl From<PackageFilters> for PackageQueryOptions {
+    fn from(f: PackageFilters) -> Self {
+        PackageQueryOptions {
+            version: f.version,
+            jdk_version: f.jdk_version,
+            distribution: f.distribution,
+            architecture: f.architecture,
+            fpu: f.fpu,
+            archive_type: f.archive_type,
+            package_type: f.package_type,
+            operating_system: f.operating_system,
+            libc_type: f.libc_type,
+            release_status: f.release_status,
+            term_of_support: f.term_of_support,
+            bitness: f.bitness,
+            javafx_bundled: f.javafx_bundled,
+            directly_downloadable: f.directly_downloadable,
+            signature_available: f.signature_available,
+            free_to_use_in_production: f.free_to_use_in_production,
+            tck_tested: f.tck_tested,
+            aqavit_certified: f.aqavit_certified,
+            feature: f.feature,
+            discovery_scope_id: f.discovery_scope_id,
+            include_versions: f.include_versions,
+            latest: f.latest,
+        }
+    }
+}
+
+// Parsed once at startup, the size difference doesn't matter here
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Subcommand)]
 enum Command {
     Packages {
         #[arg(short = 'p', long)]
         print: bool,
-        #[arg(long)]
-        version: Option<String>,
-        #[arg(long)]
-        distribution: Option<String>,
-        #[arg(long)]
-        architecture: Option<String>,
-        #[arg(long)]
-        archive_type: Option<String>,
-        #[arg(long)]
-        package_type: Option<String>,
-        #[arg(long)]
-        operating_system: Option<String>,
-        #[arg(long)]
-        libc_type: Option<String>,
-        #[arg(long)]
-        release_status: Option<String>,
-        #[arg(long)]
-        term_of_support: Option<String>,
-        #[arg(long)]
-        bitness: Option<String>,
-        #[arg(long)]
-        javafx_bundled: Option<bool>,
-        #[arg(long)]
-        directly_downloadable: Option<bool>,
-        #[arg(long)]
-        latest: Option<String>,
+        #[command(flatten)]
+        filters: PackageFilters,
+    },
+    Jdks {
+        #[arg(short = 'p', long)]
+        print: bool,
+        #[command(flatten)]
+        filters: PackageFilters,
+    },
+    Jres {
+        #[arg(short = 'p', long)]
+        print: bool,
+        #[command(flatten)]
+        filters: PackageFilters,
     },
     PackageInfo {
         package: String,
@@ -57,88 +159,83 @@ enum Command {
         #[arg(long)]
         maintained: Option<bool>,
     },
+    MajorVersion {
+        major_version: u32,
+        /// Only list the GA or EA versions
+        #[arg(long)]
+        release_status: Option<ReleaseStatus>,
+        #[arg(short = 'p', long)]
+        print: bool,
+    },
+    NamedMajorVersions {
+        /// latest_ga, latest_ea, latest_lts, latest_sts or useful
+        query: MajorVersionQuery,
+        #[arg(short = 'p', long)]
+        print: bool,
+    },
     Distributions {
         #[arg(short = 'p', long)]
         print: bool,
     },
+    DistributionsForVersion {
+        version: String,
+        #[arg(short = 'p', long)]
+        print: bool,
+    },
     DistributionInfo {
         distribution: String,
         #[arg(short = 'p', long)]
         print: bool,
-    }
+    },
+    /// Print where a package is downloaded from, without downloading it
+    DownloadUri {
+        package: String,
+    },
+    Download {
+        package: String,
+        #[arg(short = 'o', long, default_value = ".")]
+        output_dir: std::path::PathBuf,
+    },
+    /// Print installed JDKs behind the latest update of their line, as JSON
+    Audit {
+        /// Store to audit instead of the default one
+        #[arg(long)]
+        store: Option<std::path::PathBuf>,
+        /// Skip JDKs installed outside the store
+        #[arg(long)]
+        no_system: bool,
+        /// Exit with status 1 if a JDK is more than this many updates behind
+        #[arg(long)]
+        fail_behind: Option<usize>,
+    },
 }
 
 fn main() {
     let args = Args::parse();
 
-    match args.command {
-        Command::Packages {
-            print,
-            version,
-            distribution,
-            architecture,
-            archive_type,
-            package_type,
-            operating_system,
-            libc_type,
-            release_status,
-            term_of_support,
-            bitness,
-            javafx_bundled,
-            directly_downloadable,
-            latest,
-        } => {
-            println!("Pulling packages...");
-            
-            let packages = foojay_disco::pull_packages(std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from)), Some(PackageQueryOptions {
-                version,
-                distribution,
-                architecture,
-                archive_type,
-                package_type,
-                operating_system,
-                libc_type,
-                release_status,
-                term_of_support,
-                bitness,
-                javafx_bundled,
-                directly_downloadable,
-                latest,
-            })).unwrap();
+    let mut client_builder = DiscoClient::builder();
 
-            println!("Total Packages: {}", packages.result.len());
-            
-            let mut distros = vec![];
-            let mut architectures = vec![];
-            let mut operating_systems = vec![];
-            let mut versions = vec![];
-
-            for p in &packages.result {
-                if !distros.contains(&p.distribution) {
-                    distros.push(p.distribution.clone());
-                }
+    if let Some(url) = std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from)) {
+        client_builder = client_builder.base_url(url);
+    }
 
-                if !architectures.contains(&p.architecture) {
-                    architectures.push(p.architecture.clone());
-                }
+    let client = client_builder.build().unwrap();
 
-                if !operating_systems.contains(&p.operating_system) {
-                    operating_systems.push(p.operating_system.clone());
-                }
+    match args.command {
+        Command::Packages { print, filters } => {
+            println!("Pulling packages...");
 
-                if !versions.contains(&p.major_version) {
-                    versions.push(p.major_version);
-                }
-            }
+            print_packages(client.pull_packages(Some(filters.into())).unwrap(), print);
+        }
+        Command::Jdks { print, filters } => {
+            println!("Pulling JDKs...");
 
-            println!("Distributions: {:?}", distros);
-            println!("Architectures: {:?}", architectures);
-            println!("Operating Systems: {:?}", operating_systems);
-            println!("Versions: {:?}", versions);
+            print_packages(client.pull_jdks(Some(filters.into())).unwrap(), print);
+        }
+        Command::Jres { print, filters } => {
+            println!("Pulling JREs...");
 
-            if print {
-                println!("{:#?}", packages);
-            }
+            print_packages(client.pull_jres(Some(filters.into())).unwrap(), print);
         }
         Command::PackageInfo {
             package,
@@ -146,7 +243,7 @@ fn main() {
         } => {
             println!("Pulling package info...");
             
-            let package = foojay_disco::pull_package_info(std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from)), package).unwrap();
+            let package = client.pull_package_info(package).unwrap();
 
             println!("Filename: {}", package.result[0].filename);
             println!("Direct Download URI: {}", package.result[0].direct_download_uri);
@@ -168,12 +265,14 @@ fn main() {
         } => {
             println!("Pulling major versions...");
 
-            let major_versions = foojay_disco::pull_major_versions(std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from)), Some(MajorVersionsQueryOptions {
+            let major_versions = client.pull_major_versions(Some(MajorVersionsQueryOptions {
                 early_access,
                 general_availability,
                 maintained,
             })).unwrap();
 
+            print_warnings(&major_versions.warnings);
+
             let mut major_version_names = vec![];
 
             for v in &major_versions.result {
@@ -188,12 +287,55 @@ fn main() {
                 println!("{:#?}", major_versions);
             }
         }
+        Command::MajorVersion {
+            major_version,
+            release_status,
+            print,
+        } => {
+            println!("Pulling major version...");
+
+            let major_version = client.pull_major_version(major_version, release_status).unwrap();
+
+            print_warnings(&major_version.warnings);
+
+            println!("Major Version: {}", major_version.result[0].major_version);
+            println!("Term of Support: {}", major_version.result[0].term_of_support);
+            println!("Maintained: {:?}", major_version.result[0].maintained);
+            println!("Versions: {:?}", major_version.result[0].versions);
+
+            if print {
+                println!("{:#?}", major_version);
+            }
+        }
+        Command::NamedMajorVersions {
+            query,
+            print,
+        } => {
+            println!("Pulling major versions...");
+
+            let major_versions = client.pull_named_major_versions(query).unwrap();
+
+            print_warnings(&major_versions.warnings);
+
+            let major_version_names = major_versions.result
+                .iter()
+                .map(|v| v.major_version)
+                .collect::<Vec<_>>();
+
+            println!("Major Versions: {:?}", major_version_names);
+
+            if print {
+                println!("{:#?}", major_versions);
+            }
+        }
         Command::Distributions {
             print,
         } => {
             println!("Pulling distributions...");
 
-            let distributions = foojay_disco::pull_distributions(std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from))).unwrap();
+            let distributions = client.pull_distributions().unwrap();
+
+            print_warnings(&distributions.warnings);
 
             let mut distribution_names = vec![];
 
@@ -215,10 +357,9 @@ fn main() {
         } => {
             println!("Pulling distribution info...");
 
-            let distribution_info = foojay_disco::pull_distribution_info(
-                std::env::var_os(FOOJAY_URL_VAR).and_then(|u| u.to_str().map(String::from)),
-                distribution,
-            ).unwrap();
+            let distribution_info = client.pull_distribution_info(distribution).unwrap();
+
+            print_warnings(&distribution_info.warnings);
 
             println!("Distribution Name: {:?}", distribution_info.result[0].name);
             println!("Maintained: {:?}", distribution_info.result[0].maintained);
@@ -232,5 +373,124 @@ fn main() {
                 println!("{:#?}", distribution_info);
             }
         }
+        Command::DistributionsForVersion {
+            version,
+            print,
+        } => {
+            println!("Pulling distributions...");
+
+            let distributions = client.pull_distributions_for_version(version).unwrap();
+
+            print_warnings(&distributions.warnings);
+
+            let distribution_names = distributions.result
+                .iter()
+                .map(|d| d.name.clone())
+                .collect::<Vec<_>>();
+
+            println!("Distribution Names: {:?}", distribution_names);
+
+            if print {
+                println!("{:#?}", distributions);
+            }
+        }
+        Command::DownloadUri {
+            package,
+        } => {
+            println!("{}", client.package_download_uri(package).unwrap());
+        }
+        Command::Download {
+            package,
+            output_dir,
+        } => {
+            println!("Downloading package...");
+
+            let path = client.download_package_id(package, output_dir, |p| {
+                match p.total {
+                    Some(t) => print!("\r{}/{} bytes", p.downloaded, t),
+                    None => print!("\r{} bytes", p.downloaded),
+                }
+
+                let _ = std::io::stdout().flush();
+            }).unwrap();
+
+            println!();
+            println!("Saved to: {}", path.display());
+        }
+        Command::Audit {
+            store,
+            no_system,
+            fail_behind,
+        } => {
+            let store = match store {
+                Some(dir) => JdkStore::new(dir),
+                None => JdkStore::in_user_data_dir().unwrap(),
+            };
+
+            let mut targets = store.list().unwrap()
+                .iter()
+                .map(AuditTarget::from)
+                .collect::<Vec<_>>();
+
+            if !no_system {
+                targets.extend(discover_jdks().iter().map(AuditTarget::from));
+            }
+
+            let report = AuditReport::run(&client, &targets).unwrap();
+
+            println!("{}", report.to_json().unwrap());
+
+            if let Some(max) = fail_behind {
+                if report.failing(max).next().is_some() {
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn print_warnings(warnings: &[DecodeWarning]) {
+    for warning in warnings {
+        eprintln!("Skipped part of {}: {}", warning.id.as_deref().unwrap_or("?"), warning.message);
+    }
+}
+
+fn print_packages(packages: PackageList, print: bool) {
+    println!("Total Packages: {}", packages.result.len());
+
+    for warning in &packages.warnings {
+        eprintln!("Skipped package {}: {}", warning.id.as_deref().unwrap_or("?"), warning.message);
+    }
+    
+    let mut distros = vec![];
+    let mut architectures = vec![];
+    let mut operating_systems = vec![];
+    let mut versions = vec![];
+
+    for p in &packages.result {
+        if !distros.contains(&p.distribution) {
+            distros.push(p.distribution.clone());
+        }
+
+        if !architectures.contains(&p.architecture) {
+            architectures.push(p.architecture.clone());
+        }
+
+        if !operating_systems.contains(&p.operating_system) {
+            operating_systems.push(p.operating_system.clone());
+        }
+
+        if !versions.contains(&p.major_version) {
+            versions.push(p.major_version);
+        }
+    }
+
+    println!("Distributions: {:?}", distros);
+    println!("Architectures: {:?}", architectures);
+    println!("Operating Systems: {:?}", operating_systems);
+    println!("Versions: {:?}", versions);
+
+    if print {
+        println!("{:#?}", packages);
     }
 }
@@ -0,0 +1,366 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+};
+
+use crate::{
+    discover::{ReleaseInfo, SystemJdk},
+    http::{response, DiscoClient, PackageQueryOptions},
+    package::{Package, PackageList},
+    platform::Platform,
+    store::Installation,
+    types::{Architecture, OperatingSystem, PackageType, ReleaseStatus},
+    version::JavaVersion,
+    Error,
+};
+
+/// A local JDK to audit, built from an [`Installation`] or a [`SystemJdk`].
+#[derive(Debug, Clone)]
+pub struct AuditTarget {
+    /// The directory containing `bin/java`.
+    pub java_home: PathBuf,
+    /// The Disco distribution `api_parameter`.
+    pub distribution: Option<String>,
+    pub java_version: Option<JavaVersion>,
+    pub architecture: Architecture,
+    pub operating_system: OperatingSystem,
+    pub package_type: PackageType,
+}
+
+impl From<&Installation> for AuditTarget {
+    fn from(i: &Installation) -> Self {
+        match &i.package {
+            Some(p) => Self {
+                java_home: i.java_home.clone(),
+                distribution: Some(p.distribution.clone()),
+                java_version: Some(p.java_version.clone()),
+                architecture: p.architecture.normalized(),
+                operating_system: p.operating_system.clone(),
+                package_type: p.package_type.clone(),
+            },
+            None => {
+                let release = ReleaseInfo::read(&i.java_home).unwrap_or_default();
+                let (architecture, operating_system) = or_current(release.architecture(), release.operating_system());
+
+                Self {
+                    java_home: i.java_home.clone(),
+                    distribution: i.distribution.clone(),
+                    java_version: i.java_version.clone(),
+                    architecture,
+                    operating_system,
+                    package_type: release.package_type().unwrap_or(PackageType::Jdk),
+                }
+            }
+        }
+    }
+}
+
+impl From<&SystemJdk> for AuditTarget {
+    fn from(jdk: &SystemJdk) -> Self {
+        let (architecture, operating_system) = or_current(jdk.architecture.clone(), jdk.operating_system.clone());
+
+        Self {
+            java_home: jdk.java_home.clone(),
+            distribution: jdk.distribution.clone(),
+            java_version: jdk.java_version.clone(),
+            architecture,
+            operating_system,
+            package_type: jdk.package_type.clone(),
+        }
+    }
+}
+
+/// Fills in what the `release` file didn't say from the current platform,
+/// only detecting it when needed.
+fn or_current(architecture: Option<Architecture>, operating_system: Option<OperatingSystem>) -> (Architecture, OperatingSystem) {
+    match (architecture, operating_system) {
+        (Some(a), Some(o)) => (a, o),
+        (a, o) => {
+            let platform = Platform::current();
+            (a.unwrap_or(platform.architecture), o.unwrap_or(platform.operating_system))
+        }
+    }
+}
+
+/// How far an install is behind the latest update of its line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    UpToDate,
+    /// Only a vendor re-spin is missing: a later patch component or build
+    /// of the same update.
+    Patch,
+    /// A later `$FEATURE.$INTERIM.$UPDATE` exists. Update releases carry the
+    /// quarterly security fixes.
+    Security,
+    /// The install couldn't be matched against the catalog, see
+    /// [`AuditEntry::note`].
+    Unknown,
+}
+
+/// The audit result for one install.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub java_home: PathBuf,
+    pub distribution: Option<String>,
+    pub architecture: Architecture,
+    pub operating_system: OperatingSystem,
+    pub package_type: PackageType,
+    pub installed_version: Option<JavaVersion>,
+    pub latest_version: Option<JavaVersion>,
+    /// The package of [`AuditEntry::latest_version`].
+    pub latest_package_id: Option<String>,
+    pub status: AuditStatus,
+    /// Distinct `$FEATURE.$INTERIM.$UPDATE` releases of the line newer than
+    /// the installed one, up to the latest.
+    pub updates_behind: usize,
+    /// Newer builds and re-spins of the installed update, like `21.0.2+14`
+    /// or `21.0.2.1` for `21.0.2+13`. Not counted in `updates_behind`.
+    pub respins_behind: usize,
+    /// Why the status is [`AuditStatus::Unknown`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Which local JDKs are behind the latest update in their major line, for
+/// the same distribution and platform.
+///
+/// Serializes to JSON with [`AuditReport::to_json`], so CI can fail with
+/// [`AuditReport::failing`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditReport {
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditReport {
+    /// Audits `targets` against the live catalog, one query per
+    /// distribution, major version and platform.
+    pub fn run(client: &DiscoClient, targets: &[AuditTarget]) -> Result<Self, Error> {
+        Self::run_with(targets, |opts| client.pull_packages(Some(opts)))
+    }
+
+    fn run_with(
+        targets: &[AuditTarget],
+        mut pull: impl FnMut(PackageQueryOptions) -> Result<PackageList, Error>,
+    ) -> Result<Self, Error> {
+        let mut lines = HashMap::new();
+
+        for target in targets {
+            let (Some(distribution), Some(version)) = (&target.distribution, &target.java_version) else {
+                continue;
+            };
+
+            // Matching is case and alias insensitive, so are the queries
+            let key = (
+                distribution.to_lowercase(),
+                version.feature(),
+                target.architecture.normalized(),
+                target.operating_system.clone(),
+                target.package_type.clone(),
+            );
+
+            if lines.contains_key(&key) {
+                continue;
+            }
+
+            let packages = pull(PackageQueryOptions {
+                jdk_version: Some(key.1),
+                distribution: vec![key.0.clone()],
+                architecture: vec![key.2.clone()],
+                operating_system: vec![key.3.clone()],
+                package_type: vec![key.4.clone()],
+                release_status: vec![ReleaseStatus::Ga],
+                ..Default::default()
+            })?;
+
+            lines.insert(key, packages.result);
+        }
+
+        let packages = lines.into_values().flatten().collect::<Vec<_>>();
+
+        Ok(Self::from_catalog(targets, &packages))
+    }
+
+    /// Audits `targets` against `packages`, like a [`crate::CatalogSnapshot`]
+    /// listing. Packages of other lines and platforms are ignored.
+    ///
+    /// The latest version of a line is the newest package flagged
+    /// `latest_build_available`, or the newest GA package if none is.
+    pub fn from_catalog<'a>(targets: impl IntoIterator<Item = &'a AuditTarget>, packages: &[Package]) -> Self {
+        let entries = targets.into_iter().map(|t| audit(t, packages)).collect();
+
+        Self { entries }
+    }
+
+    /// Entries more than `max_updates_behind` updates behind, or that
+    /// couldn't be audited. Re-spins don't count.
+    pub fn failing(&self, max_updates_behind: usize) -> impl Iterator<Item = &AuditEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.status == AuditStatus::Unknown || e.updates_behind > max_updates_behind)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(|e| response::json_error("audit report", "", e))
+    }
+}
+
+fn audit(target: &AuditTarget, packages: &[Package]) -> AuditEntry {
+    let mut entry = AuditEntry {
+        java_home: target.java_home.clone(),
+        distribution: target.distribution.clone(),
+        architecture: target.architecture.clone(),
+        operating_system: target.operating_system.clone(),
+        package_type: target.package_type.clone(),
+        installed_version: target.java_version.clone(),
+        latest_version: None,
+        latest_package_id: None,
+        status: AuditStatus::Unknown,
+        updates_behind: 0,
+        respins_behind: 0,
+        note: None,
+    };
+
+    let (Some(distribution), Some(installed)) = (&target.distribution, &target.java_version) else {
+        entry.note = Some("unknown distribution or version".to_string());
+        return entry;
+    };
+
+    let line = packages
+        .iter()
+        .filter(|p| {
+            p.distribution.eq_ignore_ascii_case(distribution)
+                && p.java_version.feature() == installed.feature()
+                && p.architecture.normalized() == target.architecture.normalized()
+                && p.operating_system == target.operating_system
+                && p.package_type == target.package_type
+                && p.release_status == ReleaseStatus::Ga
+                && !p.java_version.is_pre_release()
+        })
+        .collect::<Vec<_>>();
+
+    let latest = line
+        .iter()
+        .filter(|p| p.latest_build_available)
+        .max_by(|a, b| a.java_version.cmp(&b.java_version))
+        .or_else(|| line.iter().max_by(|a, b| a.java_version.cmp(&b.java_version)));
+
+    let Some(latest) = latest else {
+        entry.note = Some(format!("no {} {} packages in the catalog", distribution, installed.feature()));
+        return entry;
+    };
+
+    let update = |v: &JavaVersion| (v.feature(), v.interim(), v.update());
+
+    let newer = line
+        .iter()
+        .map(|p| &p.java_version)
+        .filter(|v| *v > installed && *v <= &latest.java_version)
+        .collect::<BTreeSet<_>>();
+
+    let updates = newer.iter().map(|v| update(v)).filter(|u| *u > update(installed)).collect::<BTreeSet<_>>();
+
+    entry.status = match latest.java_version.cmp(installed) {
+        std::cmp::Ordering::Greater if update(&latest.java_version) > update(installed) => AuditStatus::Security,
+        std::cmp::Ordering::Greater => AuditStatus::Patch,
+        _ => AuditStatus::UpToDate,
+    };
+    entry.updates_behind = updates.len();
+    entry.respins_behind = newer.iter().filter(|v| update(v) == update(installed)).count();
+    entry.latest_version = Some(latest.java_version.clone());
+    entry.latest_package_id = Some(latest.id.clone());
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(id: &str, java_version: &str, latest: bool) -> Package {
+        let fields = serde_json::json!({ "latest_build_available": latest, "architecture": "amd64" });
+        crate::test_support::package(id, "temurin", java_version, fields)
+    }
+
+    fn target(java_version: &str) -> AuditTarget {
+        AuditTarget {
+            java_home: PathBuf::from("/opt/jdk"),
+            distribution: Some("temurin".to_string()),
+            java_version: JavaVersion::parse(java_version).ok(),
+            architecture: Architecture::X64,
+            operating_system: OperatingSystem::Linux,
+            package_type: PackageType::Jdk,
+        }
+    }
+
+    #[test]
+    fn audit_report_test() {
+        let packages = [
+            package("a", "21.0.1+12", false),
+            package("b", "21.0.2+13", false),
+            package("c", "21.0.2.1+1", false),
+            package("d", "21.0.3+9", true),
+            package("g", "21.0.1+13", false),
+            package("e", "17.0.10+7", true),
+        ];
+
+        let targets = [target("21.0.1+12"), target("21.0.2+13"), target("21.0.3+9"), target("11.0.22")];
+        let report = AuditReport::from_catalog(&targets, &packages);
+
+        let summary = report.entries.iter().map(|e| (e.status, e.updates_behind, e.respins_behind)).collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            (AuditStatus::Security, 2, 1),
+            (AuditStatus::Security, 1, 1),
+            (AuditStatus::UpToDate, 0, 0),
+            (AuditStatus::Unknown, 0, 0),
+        ]);
+        assert_eq!(report.entries[0].latest_package_id.as_deref(), Some("d"));
+
+        let failing = report.failing(1).map(|e| e.installed_version.as_ref().map(|v| v.to_string())).collect::<Vec<_>>();
+        assert_eq!(failing, vec![Some("21.0.1+12".to_string()), Some("11.0.22".to_string())]);
+
+        // Aliases and case don't matter for hand-built targets
+        let target = AuditTarget {
+            distribution: Some("Temurin".to_string()),
+            architecture: Architecture::Amd64,
+            ..target("21.0.3+9")
+        };
+        let report = AuditReport::from_catalog(&[target], &[package("f", "21.0.3.1+2", true)]);
+        assert_eq!((report.entries[0].status, report.entries[0].updates_behind), (AuditStatus::Patch, 0));
+        assert_eq!(report.failing(0).count(), 0);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["entries"][0]["status"], "patch");
+        assert_eq!(json["entries"][0]["latest_version"], "21.0.3.1+2");
+    }
+
+    #[test]
+    fn audit_run_queries_test() {
+        let targets = [
+            target("21.0.1+12"),
+            AuditTarget {
+                distribution: Some("Temurin".to_string()),
+                architecture: Architecture::Amd64,
+                ..target("21.0.2+13")
+            },
+        ];
+
+        let mut queries = vec![];
+
+        let report = AuditReport::run_with(&targets, |opts| {
+            queries.push(opts.to_string());
+
+            Ok(PackageList {
+                result: vec![package("a", "21.0.2+13", true)],
+                ..Default::default()
+            })
+        }).unwrap();
+
+        assert_eq!(queries.len(), 1);
+        assert!(queries[0].contains("distribution=temurin") && queries[0].contains("architecture=x64"), "{}", queries[0]);
+
+        let summary = report.entries.iter().map(|e| (e.status, e.updates_behind)).collect::<Vec<_>>();
+        assert_eq!(summary, vec![(AuditStatus::Security, 1), (AuditStatus::UpToDate, 0)]);
+    }
+}
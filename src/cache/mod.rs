@@ -0,0 +1,289 @@
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::Error;
+
+static WRITE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// On-disk cache for API responses, keyed by request URL.
+///
+/// Fresh entries (younger than the TTL) are served without a request. Stale
+/// entries are revalidated with `If-None-Match`/`If-Modified-Since`, and
+/// served as they are if the server can't be reached.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// Information about a cached response.
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    pub url: String,
+    pub fetched_at: SystemTime,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Size of the cached body in bytes.
+    pub size: usize,
+    pub fresh: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct CacheEntry {
+    url: String,
+    fetched_at: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    pub(crate) body: String,
+}
+
+pub(crate) enum Lookup {
+    Fresh(String),
+    Stale(CacheEntry),
+    Miss,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    /// A cache in the user cache directory, `$XDG_CACHE_HOME/foojay-disco/responses`
+    /// on Linux.
+    pub fn in_user_cache_dir(ttl: Duration) -> Result<Self, Error> {
+        let dir = crate::dirs::cache_dir()
+            .ok_or_else(|| Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "no user cache directory")))?;
+
+        Ok(Self::new(dir.join("responses"), ttl))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Lists all cached responses.
+    pub fn entries(&self) -> Result<Vec<CacheEntryInfo>, Error> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        Ok(dir
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+            .filter_map(|e| read_entry(&e.path()))
+            .map(|e| self.info(&e))
+            .collect())
+    }
+
+    /// Information about the cached response for `url`, if any.
+    pub fn entry(&self, url: &str) -> Option<CacheEntryInfo> {
+        read_entry(&self.path(url)).map(|e| self.info(&e))
+    }
+
+    /// Removes the cached response for `url`, returns whether there was one.
+    pub fn purge(&self, url: &str) -> Result<bool, Error> {
+        match std::fs::remove_file(self.path(url)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    /// Removes all entries older than the TTL, returns how many were removed.
+    pub fn purge_stale(&self) -> Result<usize, Error> {
+        let mut removed = 0;
+
+        for e in self.entries()?.iter().filter(|e| !e.fresh) {
+            removed += self.purge(&e.url)? as usize;
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes all entries.
+    pub fn purge_all(&self) -> Result<(), Error> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::Io(e)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn lookup(&self, url: &str) -> Lookup {
+        match read_entry(&self.path(url)) {
+            Some(e) if self.is_fresh(&e) => Lookup::Fresh(e.body),
+            Some(e) => Lookup::Stale(e),
+            None => Lookup::Miss,
+        }
+    }
+
+    /// Stores a response. Failing to write the cache never fails the request.
+    pub(crate) fn store(&self, url: &str, headers: &HeaderMap, body: &str) {
+        let header = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok()).map(String::from);
+
+        self.write(&CacheEntry {
+            url: url.to_string(),
+            fetched_at: now(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            body: body.to_string(),
+        });
+    }
+
+    /// Marks a stale entry as fresh again after a `304 Not Modified`.
+    pub(crate) fn refresh(&self, mut entry: CacheEntry) -> String {
+        entry.fetched_at = now();
+        self.write(&entry);
+
+        entry.body
+    }
+
+    fn write(&self, entry: &CacheEntry) {
+        let Ok(json) = serde_json::to_vec(entry) else {
+            return;
+        };
+
+        let path = self.path(&entry.url);
+
+        // Unique per write, processes sharing the directory may write the
+        // same entry at once
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+        let tmp = path.with_extension(format!(
+            "{}.{}.{}.tmp",
+            std::process::id(),
+            WRITE_COUNTER.fetch_add(1, Ordering::Relaxed),
+            nanos,
+        ));
+
+        let written = std::fs::create_dir_all(&self.dir)
+            .and_then(|_| std::fs::write(&tmp, json))
+            .and_then(|_| std::fs::rename(&tmp, &path));
+
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        let hash = Sha256::digest(url.as_bytes());
+
+        self.dir.join(format!("{}.json", hash.iter().map(|b| format!("{:02x}", b)).collect::<String>()))
+    }
+
+    fn is_fresh(&self, entry: &CacheEntry) -> bool {
+        now().saturating_sub(entry.fetched_at) < self.ttl.as_secs()
+    }
+
+    fn info(&self, entry: &CacheEntry) -> CacheEntryInfo {
+        CacheEntryInfo {
+            url: entry.url.clone(),
+            fetched_at: UNIX_EPOCH + Duration::from_secs(entry.fetched_at),
+            etag: entry.etag.clone(),
+            last_modified: entry.last_modified.clone(),
+            size: entry.body.len(),
+            fresh: self.is_fresh(entry),
+        }
+    }
+}
+
+impl CacheEntry {
+    /// Headers to revalidate this entry with.
+    pub(crate) fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(v) = self.etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_NONE_MATCH, v);
+        }
+
+        if let Some(v) = self.last_modified.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+            headers.insert(IF_MODIFIED_SINCE, v);
+        }
+
+        headers
+    }
+}
+
+/// Whether `e` means the server couldn't be reached at all, so a stale entry
+/// is better than nothing.
+pub(crate) fn is_network_failure(e: &Error) -> bool {
+    match e {
+        Error::Connect { .. } | Error::Timeout { .. } => true,
+        Error::Http(e) => e.is_request(),
+        Error::RetriesExhausted { source, .. } => is_network_failure(source),
+        _ => false,
+    }
+}
+
+fn read_entry(path: &Path) -> Option<CacheEntry> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_cache_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://api.foojay.io/disco/v3.0/distributions";
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+
+        let cache = ResponseCache::new(dir.path(), Duration::from_secs(60));
+        assert!(matches!(cache.lookup(url), Lookup::Miss));
+
+        cache.store(url, &headers, "{}");
+        assert!(matches!(cache.lookup(url), Lookup::Fresh(b) if b == "{}"));
+        assert_eq!(cache.entries().unwrap().len(), 1);
+
+        let expired = ResponseCache::new(dir.path(), Duration::ZERO);
+        let Lookup::Stale(entry) = expired.lookup(url) else {
+            panic!("entry should be stale");
+        };
+        assert_eq!(entry.conditional_headers().get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        assert_eq!(expired.purge_stale().unwrap(), 1);
+
+        assert!(!cache.purge(url).unwrap());
+    }
+
+    #[test]
+    fn concurrent_cache_writes_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://api.foojay.io/disco/v3.0/packages";
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let cache = ResponseCache::new(dir.path(), Duration::from_secs(60));
+                let body = i.to_string().repeat(100_000);
+
+                scope.spawn(move || cache.store(url, &HeaderMap::new(), &body));
+            }
+        });
+
+        let cache = ResponseCache::new(dir.path(), Duration::from_secs(60));
+        assert!(matches!(cache.lookup(url), Lookup::Fresh(b) if b.len() == 100_000));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,121 @@
+use sha2::Digest;
+use std::{fs::File, io::Read, path::Path};
+
+use crate::{types::ChecksumType, Error};
+
+/// Computes the lowercase hex checksum of everything `reader` yields.
+pub fn compute_checksum(reader: impl Read, checksum_type: &ChecksumType) -> Result<String, Error> {
+    match checksum_type {
+        ChecksumType::Md5 => hash::<md5::Md5>(reader),
+        ChecksumType::Sha1 => hash::<sha1::Sha1>(reader),
+        ChecksumType::Sha256 => hash::<sha2::Sha256>(reader),
+        ChecksumType::Sha512 => hash::<sha2::Sha512>(reader),
+        ChecksumType::Other(t) => Err(Error::UnsupportedChecksum(t.clone())),
+    }
+}
+
+/// Checks `reader` against `expected`, failing with [`Error::ChecksumMismatch`].
+pub fn verify_checksum(reader: impl Read, checksum_type: &ChecksumType, expected: &str) -> Result<(), Error> {
+    let actual = compute_checksum(reader, checksum_type)?;
+
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch {
+            expected: expected.trim().to_lowercase(),
+            actual,
+        })
+    }
+}
+
+pub fn verify_checksum_file(path: impl AsRef<Path>, checksum_type: &ChecksumType, expected: &str) -> Result<(), Error> {
+    verify_checksum(File::open(path).map_err(Error::Io)?, checksum_type, expected)
+}
+
+/// Guesses the checksum type from the length of a hex checksum, for packages
+/// that don't state one.
+pub fn infer_checksum_type(checksum: &str) -> Option<ChecksumType> {
+    match checksum.trim().len() {
+        32 => Some(ChecksumType::Md5),
+        40 => Some(ChecksumType::Sha1),
+        64 => Some(ChecksumType::Sha256),
+        128 => Some(ChecksumType::Sha512),
+        _ => None,
+    }
+}
+
+/// Extracts the checksum for `filename` from a checksum file.
+///
+/// Accepts a bare checksum as well as the `hash  filename` (and `hash *filename`)
+/// lines written by `sha256sum` and friends. Lines for other files are skipped.
+pub fn parse_checksum_file(content: &str, filename: &str) -> Option<String> {
+    let mut fallback = None;
+
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+
+        if !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+
+        match parts.next().map(|f| f.trim_start_matches('*')) {
+            Some(f) if f == filename || f.rsplit('/').next() == Some(filename) => {
+                return Some(hash.to_lowercase());
+            }
+            Some(_) => {}
+            None => {
+                fallback.get_or_insert(hash.to_lowercase());
+            }
+        }
+    }
+
+    fallback
+}
+
+fn hash<D: Digest>(mut reader: impl Read) -> Result<String, Error> {
+    let mut hasher = D::new();
+    let mut buf = vec![0; 64 * 1024];
+
+    loop {
+        let n = reader.read(&mut buf).map_err(Error::Io)?;
+
+        if n == 0 {
+            break;
+        }
+
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_checksum_test() {
+        assert_eq!(compute_checksum(&b"abc"[..], &ChecksumType::Md5).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(compute_checksum(&b"abc"[..], &ChecksumType::Sha1).unwrap(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            compute_checksum(&b"abc"[..], &ChecksumType::Sha256).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+
+        assert!(verify_checksum(&b"abc"[..], &ChecksumType::Md5, "900150983CD24FB0D6963F7D28E17F72").is_ok());
+        assert!(matches!(
+            verify_checksum(&b"abd"[..], &ChecksumType::Md5, "900150983cd24fb0d6963f7d28e17f72"),
+            Err(Error::ChecksumMismatch { .. }),
+        ));
+    }
+
+    #[test]
+    fn parse_checksum_file_test() {
+        let file = "aaaa  other.tar.gz\nbbbb *OpenJDK17U-jdk_x64_linux.tar.gz\n";
+        assert_eq!(parse_checksum_file(file, "OpenJDK17U-jdk_x64_linux.tar.gz"), Some("bbbb".to_string()));
+        assert_eq!(parse_checksum_file(file, "missing.zip"), None);
+
+        assert_eq!(parse_checksum_file("CCCC\n", "anything.zip"), Some("cccc".to_string()));
+    }
+}
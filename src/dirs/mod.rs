@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+const APP_DIR: &str = "foojay-disco";
+
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .filter(|d| d.is_absolute())
+}
+
+/// Per-user cache directory for this crate, following the XDG base directory
+/// spec on Linux and the platform conventions elsewhere.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Caches"))
+    } else if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else {
+        env_dir("XDG_CACHE_HOME").or_else(|| home_dir().map(|h| h.join(".cache")))
+    };
+
+    base.map(|b| b.join(APP_DIR))
+}
+
+/// Per-user data directory for this crate, `$XDG_DATA_HOME/foojay-disco` on
+/// Linux.
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "macos") {
+        home_dir().map(|h| h.join("Library").join("Application Support"))
+    } else if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| home_dir().map(|h| h.join(".local").join("share")))
+    };
+
+    base.map(|b| b.join(APP_DIR))
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    extract,
+    types::{Architecture, OperatingSystem, PackageType},
+    version::JavaVersion,
+};
+
+/// Where a discovered JDK was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JdkSource {
+    /// The `JAVA_HOME` environment variable.
+    JavaHome,
+    /// `/usr/lib/jvm`, where Linux packages install.
+    SystemPackages,
+    /// `/Library/Java/JavaVirtualMachines` on macOS.
+    MacOsFramework,
+    /// `/opt`.
+    Opt,
+    /// `~/.sdkman/candidates/java`.
+    Sdkman,
+    /// `~/.asdf/installs/java`.
+    Asdf,
+    /// `~/.jdks`, used by IntelliJ IDEA.
+    IntelliJ,
+    /// `~/.gradle/jdks`, Gradle's toolchain downloads.
+    Gradle,
+    /// A directory passed by the caller.
+    Custom,
+}
+
+/// The key-value pairs of a JDK's `release` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub values: HashMap<String, String>,
+}
+
+impl ReleaseInfo {
+    /// Parses `KEY="value"` lines, quotes are optional.
+    pub fn parse(content: &str) -> Self {
+        let values = content
+            .lines()
+            .filter_map(|l| l.split_once('='))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+            .collect();
+
+        Self { values }
+    }
+
+    /// Reads `<java_home>/release`, `None` if there is none.
+    pub fn read(java_home: impl AsRef<Path>) -> Option<Self> {
+        std::fs::read_to_string(java_home.as_ref().join("release"))
+            .ok()
+            .map(|c| Self::parse(&c))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str).filter(|v| !v.is_empty())
+    }
+
+    /// `JAVA_RUNTIME_VERSION` if present (it includes the build), otherwise
+    /// `JAVA_VERSION`.
+    pub fn java_version(&self) -> Option<JavaVersion> {
+        ["JAVA_RUNTIME_VERSION", "JAVA_VERSION"]
+            .iter()
+            .find_map(|k| JavaVersion::parse(self.get(k)?).ok())
+    }
+
+    /// The Disco distribution `api_parameter`, from `IMPLEMENTOR` and a few
+    /// vendor specific keys.
+    pub fn distribution(&self) -> Option<&'static str> {
+        let implementor = self.get("IMPLEMENTOR")?.to_lowercase();
+        let implementor_version = self.get("IMPLEMENTOR_VERSION").unwrap_or_default().to_lowercase();
+
+        let distribution = match implementor.as_str() {
+            i if i.starts_with("eclipse adoptium") || i == "eclipse foundation" => "temurin",
+            i if i.starts_with("adoptopenjdk") => "aoj",
+            i if i.starts_with("azul") && implementor_version.contains("prime") => "zulu_prime",
+            i if i.starts_with("azul") => "zulu",
+            i if i.starts_with("amazon") => "corretto",
+            i if i.starts_with("bellsoft") => "liberica",
+            i if i.starts_with("microsoft") => "microsoft",
+            i if i.starts_with("sap") => "sap_machine",
+            i if i.starts_with("international business machines") || i.starts_with("ibm") => "semeru",
+            i if i.starts_with("jetbrains") => "jetbrains",
+            i if i.starts_with("alibaba") => "dragonwell",
+            i if i.starts_with("tencent") => "kona",
+            i if i.starts_with("huawei") => "bisheng",
+            i if i.starts_with("red hat") && implementor_version.contains("mandrel") => "mandrel",
+            i if i.starts_with("red hat") => "redhat",
+            i if i.starts_with("openlogic") => "openlogic",
+            i if i.starts_with("graalvm community") => "graalvm_community",
+            i if i.starts_with("oracle") && self.get("GRAALVM_VERSION").is_some() => "graalvm",
+            i if i.starts_with("oracle") && self.get("BUILD_TYPE") == Some("commercial") => "oracle",
+            i if i.starts_with("oracle") => "oracle_open_jdk",
+            _ => return None,
+        };
+
+        Some(distribution)
+    }
+
+    /// `OS_ARCH`, with aliases like `x86_64` normalized.
+    pub fn architecture(&self) -> Option<Architecture> {
+        self.get("OS_ARCH").map(|a| Architecture::from(a.replace('_', "-")).normalized())
+    }
+
+    pub fn operating_system(&self) -> Option<OperatingSystem> {
+        Some(match self.get("OS_NAME")?.to_lowercase().as_str() {
+            "darwin" | "mac os x" => OperatingSystem::Macos,
+            os if os.starts_with("windows") => OperatingSystem::Windows,
+            "sunos" => OperatingSystem::Solaris,
+            os => OperatingSystem::from(os),
+        })
+    }
+
+    /// `IMAGE_TYPE`, `JDK` or `JRE`.
+    pub fn package_type(&self) -> Option<PackageType> {
+        self.get("IMAGE_TYPE").map(PackageType::from)
+    }
+}
+
+/// A JDK found on the system.
+#[derive(Debug, Clone)]
+pub struct SystemJdk {
+    /// The directory containing `bin/java`.
+    pub java_home: PathBuf,
+    pub source: JdkSource,
+    pub java_version: Option<JavaVersion>,
+    /// The Disco distribution `api_parameter`, `None` for unknown vendors.
+    pub distribution: Option<String>,
+    pub architecture: Option<Architecture>,
+    pub operating_system: Option<OperatingSystem>,
+    pub package_type: PackageType,
+    pub release: ReleaseInfo,
+}
+
+impl SystemJdk {
+    /// Identifies the JDK at `java_home`, `None` if there is no `bin/java`.
+    pub fn identify(java_home: impl AsRef<Path>, source: JdkSource) -> Option<Self> {
+        let java_home = extract::find_java_home(java_home)?;
+        let release = ReleaseInfo::read(&java_home).unwrap_or_default();
+
+        let has_javac = java_home.join("bin").join("javac").is_file() || java_home.join("bin").join("javac.exe").is_file();
+
+        Some(Self {
+            java_version: release.java_version(),
+            distribution: release.distribution().map(String::from),
+            architecture: release.architecture(),
+            operating_system: release.operating_system(),
+            package_type: release.package_type().unwrap_or(if has_javac { PackageType::Jdk } else { PackageType::Jre }),
+            java_home,
+            source,
+            release,
+        })
+    }
+}
+
+/// The locations [`discover_jdks`] scans. Directories holding JDKs, except
+/// for `JAVA_HOME`, which is a JDK itself.
+pub fn default_locations() -> Vec<(JdkSource, PathBuf)> {
+    let mut locations = vec![];
+
+    if let Some(java_home) = std::env::var_os("JAVA_HOME").filter(|h| !h.is_empty()) {
+        locations.push((JdkSource::JavaHome, PathBuf::from(java_home)));
+    }
+
+    locations.push((JdkSource::SystemPackages, PathBuf::from("/usr/lib/jvm")));
+    locations.push((JdkSource::MacOsFramework, PathBuf::from("/Library/Java/JavaVirtualMachines")));
+    locations.push((JdkSource::Opt, PathBuf::from("/opt")));
+
+    if let Some(home) = crate::dirs::home_dir() {
+        locations.extend([
+            (JdkSource::Sdkman, home.join(".sdkman").join("candidates").join("java")),
+            (JdkSource::Asdf, home.join(".asdf").join("installs").join("java")),
+            (JdkSource::IntelliJ, home.join(".jdks")),
+            (JdkSource::Gradle, home.join(".gradle").join("jdks")),
+        ]);
+    }
+
+    locations
+}
+
+/// Scans the [`default_locations`] for installed JDKs.
+pub fn discover_jdks() -> Vec<SystemJdk> {
+    discover_jdks_in(&default_locations())
+}
+
+/// Scans `locations` for installed JDKs. [`JdkSource::JavaHome`] entries are
+/// identified directly, others are scanned one level deep.
+///
+/// JDKs reachable through several paths, like sdkman's `current` symlink,
+/// are reported once, from the first location.
+pub fn discover_jdks_in(locations: &[(JdkSource, PathBuf)]) -> Vec<SystemJdk> {
+    let mut seen = HashSet::new();
+    let mut found = vec![];
+
+    for (source, dir) in locations {
+        let candidates = match source {
+            JdkSource::JavaHome => vec![dir.clone()],
+            _ => match std::fs::read_dir(dir) {
+                Ok(entries) => {
+                    let mut dirs = entries
+                        .filter_map(Result::ok)
+                        .map(|e| e.path())
+                        .filter(|p| p.is_dir())
+                        .collect::<Vec<_>>();
+                    dirs.sort();
+                    dirs
+                }
+                Err(_) => continue,
+            },
+        };
+
+        for candidate in candidates {
+            let Some(jdk) = SystemJdk::identify(&candidate, *source) else {
+                continue;
+            };
+
+            let key = jdk.java_home.canonicalize().unwrap_or_else(|_| jdk.java_home.clone());
+
+            if seen.insert(key) {
+                found.push(jdk);
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_info_test() {
+        let release = ReleaseInfo::parse(concat!(
+            "IMPLEMENTOR=\"Eclipse Adoptium\"\n",
+            "IMPLEMENTOR_VERSION=\"Temurin-21.0.2+13\"\n",
+            "JAVA_RUNTIME_VERSION=\"21.0.2+13-LTS\"\n",
+            "JAVA_VERSION=\"21.0.2\"\n",
+            "OS_ARCH=\"x86_64\"\n",
+            "OS_NAME=\"Linux\"\n",
+            "IMAGE_TYPE=\"JDK\"\n",
+        ));
+
+        assert_eq!(release.java_version().unwrap().build(), Some(13));
+        assert_eq!(release.distribution(), Some("temurin"));
+        assert_eq!(release.architecture(), Some(Architecture::X64));
+        assert_eq!(release.operating_system(), Some(OperatingSystem::Linux));
+        assert_eq!(release.package_type(), Some(PackageType::Jdk));
+
+        let release = ReleaseInfo::parse("IMPLEMENTOR=\"Oracle Corporation\"\nJAVA_VERSION=\"1.8.0_392\"\nOS_ARCH=\"amd64\"");
+        assert_eq!(release.distribution(), Some("oracle_open_jdk"));
+        assert_eq!(release.java_version().unwrap().feature(), 8);
+        assert_eq!(release.architecture(), Some(Architecture::X64));
+    }
+
+    #[test]
+    fn discover_jdks_test() {
+        let root = tempfile::tempdir().unwrap();
+
+        for (name, release) in [("zulu-17", "IMPLEMENTOR=\"Azul Systems, Inc.\"\nJAVA_VERSION=\"17.0.9\""), ("jre-11", "JAVA_VERSION=\"11.0.21\"")] {
+            let bin = root.path().join(name).join("bin");
+            std::fs::create_dir_all(&bin).unwrap();
+            std::fs::write(bin.join("java"), "").unwrap();
+            std::fs::write(root.path().join(name).join("release"), release).unwrap();
+        }
+
+        std::fs::write(root.path().join("zulu-17/bin/javac"), "").unwrap();
+        std::fs::create_dir(root.path().join("not-a-jdk")).unwrap();
+
+        let found = discover_jdks_in(&[
+            (JdkSource::JavaHome, root.path().join("zulu-17")),
+            (JdkSource::Custom, root.path().to_path_buf()),
+        ]);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].source, found[0].distribution.as_deref()), (JdkSource::JavaHome, Some("zulu")));
+        assert_eq!(found[0].package_type, PackageType::Jdk);
+        assert_eq!((found[1].distribution.as_deref(), &found[1].package_type), (None, &PackageType::Jre));
+    }
+}
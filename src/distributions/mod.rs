@@ -1,37 +1,133 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::{
+    schema::{self, DecodeWarning, RawList, Schema, SchemaReport},
+    version::JavaVersion,
+};
+
+/// A list of distributions. Entries that fail to decode are skipped and
+/// reported in `warnings`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(from = "RawList")]
 pub struct DistributionList {
     pub result: Vec<Distribution>,
     pub message: String,
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "RawList")]
 pub struct DistributionInfo {
     pub result: [Distribution; 1],
     pub message: String,
+    /// Versions that failed to decode and were left out.
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
 }
 
+/// A distribution. Only `api_parameter` is required.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Distribution {
+    #[serde(default)]
     pub name: String,
     pub api_parameter: String,
+    #[serde(default)]
     pub maintained: bool,
+    #[serde(default)]
     pub available: bool,
+    #[serde(default)]
     pub build_of_openjdk: bool,
+    #[serde(default)]
     pub build_of_graalvm: bool,
+    #[serde(default)]
     pub official_uri: String,
-    pub versions: Vec<String>,
+    /// Versions that fail to parse are left out and reported in the
+    /// containing response's `warnings`.
+    #[serde(default)]
+    pub versions: Vec<JavaVersion>,
+}
+
+impl From<RawList> for DistributionList {
+    fn from(mut raw: RawList) -> Self {
+        let mut warnings = schema::strip_invalid_elements::<JavaVersion>(&mut raw.result, "versions");
+        let (result, item_warnings) = schema::decode_items(raw.result);
+        warnings.extend(item_warnings);
+
+        Self {
+            result,
+            message: raw.message,
+            warnings,
+        }
+    }
+}
+
+impl TryFrom<RawList> for DistributionInfo {
+    type Error = String;
+
+    fn try_from(mut raw: RawList) -> Result<Self, Self::Error> {
+        let warnings = schema::strip_invalid_elements::<JavaVersion>(&mut raw.result, "versions");
+        let (distribution, message) = schema::decode_single::<Distribution>(raw)?;
+
+        Ok(Self {
+            result: [distribution],
+            message,
+            warnings,
+        })
+    }
+}
+
+impl Schema for Distribution {
+    const FIELDS: &'static [&'static str] = &[
+        "name",
+        "api_parameter",
+        "maintained",
+        "available",
+        "build_of_openjdk",
+        "build_of_graalvm",
+        "official_uri",
+        "versions",
+    ];
 }
 
 impl DistributionList {
     pub fn decode(json: String) -> Result<Self, serde_json::Error> {
         serde_json::from_str(&json)
     }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        let mut report = schema::check_list::<Distribution>(json)?;
+        report.warnings = Self::decode(json.to_string())?.warnings;
+
+        Ok(report)
+    }
 }
 
 impl DistributionInfo {
     pub fn decode(json: String) -> Result<Self, serde_json::Error> {
         serde_json::from_str(&json)
     }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        schema::check_list::<Distribution>(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_lenient_versions_test() {
+        let list = DistributionList::decode(r#"{"result": [
+            {"api_parameter": "zulu", "versions": ["21.0.2+13", "not a version", null, "17"]}
+        ]}"#.to_string()).unwrap();
+
+        assert_eq!(list.warnings.len(), 1);
+        assert_eq!((list.warnings[0].id.as_deref(), list.warnings[0].index), (Some("zulu"), 0));
+        assert!(list.warnings[0].message.starts_with("versions[1]"));
+        assert_eq!(list.result[0].versions.iter().map(|v| v.to_string()).collect::<Vec<_>>(), vec!["21.0.2+13", "17"]);
+    }
 }
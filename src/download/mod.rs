@@ -0,0 +1,117 @@
+use reqwest::blocking::Client;
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{http::response, Error};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of a running download, passed to the progress callback after
+/// every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Bytes written so far.
+    pub downloaded: u64,
+    /// Expected size, from `Package::size` or the `Content-Length` header.
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Progress between 0.0 and 1.0, if the total size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|t| *t > 0)
+            .map(|t| (self.downloaded as f64 / t as f64).min(1.0))
+    }
+}
+
+/// Joins `dir` and `filename`, refusing names that would escape `dir`.
+pub(crate) fn target_path(dir: &Path, filename: &str) -> Result<PathBuf, Error> {
+    match Path::new(filename).file_name() {
+        Some(name) if name == filename => Ok(dir.join(name)),
+        _ => Err(Error::InvalidFilename(filename.to_string())),
+    }
+}
+
+/// Streams `uri` into `path`.
+///
+/// Data is written to `<path>.part` first and renamed once complete, so
+/// `path` only ever exists with the full content.
+pub(crate) fn download_to(
+    client: &Client,
+    uri: &str,
+    path: &Path,
+    expected_len: Option<u64>,
+    mut progress: impl FnMut(DownloadProgress),
+) -> Result<(), Error> {
+    let mut response = response::send(client, uri)?;
+
+    let total = expected_len.or(response.content_length());
+
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    let part_path = PathBuf::from(part_path);
+
+    let result = (|| {
+        let mut file = BufWriter::new(File::create(&part_path).map_err(Error::Io)?);
+        let mut buf = vec![0; CHUNK_SIZE];
+        let mut downloaded = 0;
+
+        progress(DownloadProgress { downloaded, total });
+
+        loop {
+            let n = response.read(&mut buf).map_err(Error::Io)?;
+
+            if n == 0 {
+                break;
+            }
+
+            file.write_all(&buf[..n]).map_err(Error::Io)?;
+            downloaded += n as u64;
+
+            progress(DownloadProgress { downloaded, total });
+        }
+
+        file.into_inner()
+            .map_err(|e| Error::Io(e.into_error()))?
+            .sync_all()
+            .map_err(Error::Io)?;
+
+        std::fs::rename(&part_path, path).map_err(Error::Io)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&part_path);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_path_test() {
+        let dir = Path::new("/tmp/jdks");
+
+        assert_eq!(target_path(dir, "jdk.tar.gz").unwrap(), dir.join("jdk.tar.gz"));
+
+        for name in ["../x", "a/../../x", "/etc/passwd", "sub/jdk.zip", "..", ".", ""] {
+            assert!(matches!(target_path(dir, name), Err(Error::InvalidFilename(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn progress_fraction_test() {
+        let progress = |downloaded, total| DownloadProgress { downloaded, total }.fraction();
+
+        assert_eq!(progress(50, Some(200)), Some(0.25));
+        assert_eq!(progress(300, Some(200)), Some(1.0));
+        assert_eq!(progress(50, Some(0)), None);
+        assert_eq!(progress(50, None), None);
+    }
+}
@@ -0,0 +1,397 @@
+use flate2::read::GzDecoder;
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{types::ArchiveType, Error};
+
+/// Extracts a downloaded package into `dest` and returns the JDK home, the
+/// directory containing `bin/java`.
+///
+/// Supports `tar.gz`, `tgz`, `tar` and `zip` archives. A single top-level
+/// directory is stripped, so `dest` holds the archive's content directly. For
+/// macOS bundles the returned home is `Contents/Home`, on any host.
+///
+/// Entries that would land outside `dest`, including through symlinks and
+/// hard links, fail the extraction with [`Error::UnsafeArchiveEntry`]. On
+/// any failure what this call created is removed: `dest` itself if it didn't
+/// exist before, otherwise the top-level entries that weren't there.
+pub fn extract_archive(
+    archive: impl AsRef<Path>,
+    archive_type: &ArchiveType,
+    dest: impl AsRef<Path>,
+) -> Result<PathBuf, Error> {
+    let archive = archive.as_ref();
+    let dest = dest.as_ref();
+
+    let existing = match std::fs::read_dir(dest) {
+        Ok(entries) => Some(entries.map(|e| e.map(|e| e.file_name())).collect::<Result<HashSet<_>, _>>().map_err(Error::Io)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::Io(e)),
+    };
+
+    std::fs::create_dir_all(dest).map_err(Error::Io)?;
+
+    let result = (|| {
+        let file = BufReader::new(File::open(archive).map_err(Error::Io)?);
+
+        match archive_type {
+            ArchiveType::TarGz | ArchiveType::Tgz => extract_tar(GzDecoder::new(file), dest)?,
+            ArchiveType::Tar => extract_tar(file, dest)?,
+            ArchiveType::Zip => extract_zip(file, dest)?,
+            t => return Err(Error::UnsupportedArchive(t.to_string())),
+        }
+
+        check_symlinks(dest)?;
+        strip_top_level_dir(dest)?;
+
+        find_java_home(dest).ok_or_else(|| Error::JavaHomeNotFound(dest.to_path_buf()))
+    })();
+
+    if result.is_err() {
+        remove_created(dest, existing.as_ref());
+    }
+
+    result
+}
+
+/// Removes `dest`, or only the top-level entries not in `existing`.
+fn remove_created(dest: &Path, existing: Option<&HashSet<OsString>>) {
+    let Some(existing) = existing else {
+        let _ = std::fs::remove_dir_all(dest);
+        return;
+    };
+
+    for e in std::fs::read_dir(dest).into_iter().flatten().flatten() {
+        if existing.contains(&e.file_name()) {
+            continue;
+        }
+
+        // Doesn't follow symlinks, so only the link itself goes
+        let _ = match e.file_type() {
+            Ok(t) if t.is_dir() => std::fs::remove_dir_all(e.path()),
+            _ => std::fs::remove_file(e.path()),
+        };
+    }
+}
+
+/// Finds the directory containing `bin/java` below `dir`, preferring
+/// `dir` itself and the macOS `Contents/Home` layout.
+pub fn find_java_home(dir: impl AsRef<Path>) -> Option<PathBuf> {
+    let dir = dir.as_ref();
+
+    let is_home = |d: &Path| d.join("bin").join("java").is_file() || d.join("bin").join("java.exe").is_file();
+
+    for candidate in [dir.to_path_buf(), dir.join("Contents").join("Home")] {
+        if is_home(&candidate) {
+            return Some(candidate);
+        }
+    }
+
+    // Some archives nest the JDK a bit deeper, e.g. `jdk/jre` layouts
+    let mut queue = vec![(dir.to_path_buf(), 0)];
+
+    while let Some((d, depth)) = queue.pop() {
+        if is_home(&d) {
+            return Some(d);
+        }
+
+        if depth < 3 {
+            if let Ok(entries) = std::fs::read_dir(&d) {
+                for e in entries.flatten() {
+                    if e.file_type().is_ok_and(|t| t.is_dir()) {
+                        queue.push((e.path(), depth + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn extract_tar(reader: impl Read, dest: &Path) -> Result<(), Error> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries().map_err(Error::Io)? {
+        let mut entry = entry.map_err(Error::Io)?;
+        let entry_path = entry.path().map_err(Error::Io)?.into_owned();
+
+        let Some(rel) = sanitize(&entry_path)? else {
+            continue;
+        };
+
+        let target = dest.join(&rel);
+        let entry_type = entry.header().entry_type();
+
+        if entry_type.is_dir() {
+            std::fs::create_dir_all(&target).map_err(Error::Io)?;
+            continue;
+        }
+
+        prepare_parent(dest, &target)?;
+
+        if entry_type.is_symlink() {
+            let link = entry.link_name().map_err(Error::Io)?
+                .ok_or_else(|| Error::UnsafeArchiveEntry(entry_path.display().to_string()))?;
+
+            check_link_target(&rel, &link)?;
+            create_symlink(&link, &target)?;
+        } else if entry_type.is_hard_link() {
+            let link = entry.link_name().map_err(Error::Io)?
+                .ok_or_else(|| Error::UnsafeArchiveEntry(entry_path.display().to_string()))?;
+
+            let unsafe_link = || Error::UnsafeArchiveEntry(format!("{} => {}", rel.display(), link.display()));
+
+            let source = sanitize(&link)?.ok_or_else(unsafe_link)?;
+
+            // The source may run through symlinks extracted earlier
+            let source = dest.join(source).canonicalize().map_err(Error::Io)?;
+
+            if !source.starts_with(dest.canonicalize().map_err(Error::Io)?) {
+                return Err(unsafe_link());
+            }
+
+            std::fs::hard_link(source, &target).map_err(Error::Io)?;
+        } else if entry_type.is_file() || entry_type.is_contiguous() {
+            entry.unpack(&target).map_err(Error::Io)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip(reader: impl Read + std::io::Seek, dest: &Path) -> Result<(), Error> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(Error::Zip)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(Error::Zip)?;
+
+        let Some(rel) = sanitize(Path::new(file.name()))? else {
+            continue;
+        };
+
+        let target = dest.join(&rel);
+
+        if file.is_dir() {
+            std::fs::create_dir_all(&target).map_err(Error::Io)?;
+            continue;
+        }
+
+        prepare_parent(dest, &target)?;
+
+        if file.is_symlink() {
+            let mut link = String::new();
+            file.read_to_string(&mut link).map_err(Error::Io)?;
+
+            check_link_target(&rel, Path::new(&link))?;
+            create_symlink(Path::new(&link), &target)?;
+            continue;
+        }
+
+        let mut out = File::create(&target).map_err(Error::Io)?;
+        std::io::copy(&mut file, &mut out).map_err(Error::Io)?;
+
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&target, std::fs::Permissions::from_mode(mode & 0o7777)).map_err(Error::Io)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Turns an archive path into a relative path without `..`, `None` for the
+/// archive root itself.
+fn sanitize(path: &Path) -> Result<Option<PathBuf>, Error> {
+    let mut out = PathBuf::new();
+
+    for c in path.components() {
+        match c {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            _ => return Err(Error::UnsafeArchiveEntry(path.display().to_string())),
+        }
+    }
+
+    Ok((!out.as_os_str().is_empty()).then_some(out))
+}
+
+/// Checks that a symlink at `rel` pointing to `link` stays inside the
+/// extraction directory.
+fn check_link_target(rel: &Path, link: &Path) -> Result<(), Error> {
+    let unsafe_link = || Error::UnsafeArchiveEntry(format!("{} -> {}", rel.display(), link.display()));
+
+    if link.has_root() {
+        return Err(unsafe_link());
+    }
+
+    let mut depth = rel.components().count() - 1;
+
+    for c in link.components() {
+        match c {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => depth = depth.checked_sub(1).ok_or_else(unsafe_link)?,
+            _ => return Err(unsafe_link()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the parent of `target` and makes sure it resolves inside `dest`,
+/// so earlier symlinks can't redirect writes. Removes whatever is at `target`.
+fn prepare_parent(dest: &Path, target: &Path) -> Result<(), Error> {
+    let parent = target.parent().unwrap_or(dest);
+
+    std::fs::create_dir_all(parent).map_err(Error::Io)?;
+
+    let dest_real = dest.canonicalize().map_err(Error::Io)?;
+
+    if !parent.canonicalize().map_err(Error::Io)?.starts_with(&dest_real) {
+        return Err(Error::UnsafeArchiveEntry(target.display().to_string()));
+    }
+
+    if target.symlink_metadata().is_ok() {
+        std::fs::remove_file(target).map_err(Error::Io)?;
+    }
+
+    Ok(())
+}
+
+/// Checks every symlink below `dest` resolves inside it, after all entries
+/// are in place.
+fn check_symlinks(dest: &Path) -> Result<(), Error> {
+    let dest_real = dest.canonicalize().map_err(Error::Io)?;
+    let mut queue = vec![dest.to_path_buf()];
+
+    while let Some(d) = queue.pop() {
+        for e in std::fs::read_dir(&d).map_err(Error::Io)? {
+            let e = e.map_err(Error::Io)?;
+            let file_type = e.file_type().map_err(Error::Io)?;
+
+            if file_type.is_symlink() {
+                // Dangling links can't be followed, so they can't escape either
+                if let Ok(real) = e.path().canonicalize() {
+                    if !real.starts_with(&dest_real) {
+                        return Err(Error::UnsafeArchiveEntry(e.path().display().to_string()));
+                    }
+                }
+            } else if file_type.is_dir() {
+                queue.push(e.path());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves the content of a single top-level directory up into `dest`.
+fn strip_top_level_dir(dest: &Path) -> Result<(), Error> {
+    let entries = std::fs::read_dir(dest)
+        .map_err(Error::Io)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::Io)?;
+
+    let [top] = entries.as_slice() else {
+        return Ok(());
+    };
+
+    if !top.file_type().map_err(Error::Io)?.is_dir() {
+        return Ok(());
+    }
+
+    // Rename first, the directory may contain an entry with its own name
+    let tmp = dest.join(".foojay-disco-strip");
+    std::fs::rename(top.path(), &tmp).map_err(Error::Io)?;
+
+    for e in std::fs::read_dir(&tmp).map_err(Error::Io)? {
+        let e = e.map_err(Error::Io)?;
+        std::fs::rename(e.path(), dest.join(e.file_name())).map_err(Error::Io)?;
+    }
+
+    std::fs::remove_dir(&tmp).map_err(Error::Io)
+}
+
+#[cfg(unix)]
+fn create_symlink(link: &Path, target: &Path) -> Result<(), Error> {
+    std::os::unix::fs::symlink(link, target).map_err(Error::Io)
+}
+
+// JDK archives for Windows don't use symlinks, and creating them needs
+// extra privileges there
+#[cfg(not(unix))]
+fn create_symlink(_link: &Path, _target: &Path) -> Result<(), Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tar_gz;
+
+    fn extract(archive: Vec<u8>) -> (tempfile::TempDir, Result<PathBuf, Error>) {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("archive.tar.gz");
+        std::fs::write(&archive_path, archive).unwrap();
+
+        let result = extract_archive(&archive_path, &ArchiveType::TarGz, dir.path().join("jdk"));
+
+        (dir, result)
+    }
+
+    #[test]
+    fn extract_archive_test() {
+        let (dir, home) = extract(tar_gz(
+            &[("jdk-17.0.9+9/bin/java", b"java"), ("jdk-17.0.9+9/release", b"JAVA_VERSION=\"17.0.9\"")],
+            &[(tar::EntryType::Symlink, "jdk-17.0.9+9/bin/javac", "java")],
+        ));
+
+        assert_eq!(home.unwrap(), dir.path().join("jdk"));
+        assert!(dir.path().join("jdk/release").is_file());
+        assert!(dir.path().join("jdk/bin/javac").is_file());
+    }
+
+    #[test]
+    fn extract_macos_archive_test() {
+        let (dir, home) = extract(tar_gz(&[("jdk-21.jdk/Contents/Home/bin/java", b"java")], &[]));
+
+        assert_eq!(home.unwrap(), dir.path().join("jdk/Contents/Home"));
+    }
+
+    #[test]
+    fn extract_unsafe_archive_test() {
+        let (_dir, result) = extract(tar_gz(&[("../evil", b"evil")], &[]));
+        assert!(matches!(result, Err(Error::UnsafeArchiveEntry(_))));
+
+        let (_dir, result) = extract(tar_gz(&[], &[(tar::EntryType::Symlink, "jdk/escape", "../../outside")]));
+        assert!(matches!(result, Err(Error::UnsafeArchiveEntry(_))));
+
+        // Each symlink stays inside on its own, the hard link through both doesn't
+        let (dir, result) = extract(tar_gz(&[], &[
+            (tar::EntryType::Symlink, "s", "."),
+            (tar::EntryType::Symlink, "t", "s/.."),
+            (tar::EntryType::Link, "leak", "t/archive.tar.gz"),
+        ]));
+        assert!(matches!(result, Err(Error::UnsafeArchiveEntry(_))));
+        assert!(!dir.path().join("jdk").exists());
+
+        // Extracting into an existing directory keeps what was there
+        std::fs::create_dir(dir.path().join("jdk")).unwrap();
+        std::fs::write(dir.path().join("jdk/keep"), b"keep").unwrap();
+        std::fs::write(dir.path().join("archive.tar.gz"), tar_gz(&[("jdk/bin/java", b"java")], &[
+            (tar::EntryType::Symlink, "escape", "../.."),
+        ])).unwrap();
+
+        let result = extract_archive(dir.path().join("archive.tar.gz"), &ArchiveType::TarGz, dir.path().join("jdk"));
+        assert!(matches!(result, Err(Error::UnsafeArchiveEntry(_))));
+        assert_eq!(std::fs::read_dir(dir.path().join("jdk")).unwrap().count(), 1);
+        assert!(dir.path().join("jdk/keep").is_file());
+    }
+}
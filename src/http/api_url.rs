@@ -1,201 +1,304 @@
-use url::Url;
-
-use crate::Error;
-
-#[derive(Debug, Clone)]
+use url::{form_urlencoded, Url};
+
+use crate::{
+    types::{Architecture, ArchiveType, Bitness, Fpu, LibCType, Latest, MajorVersionQuery, OperatingSystem, PackageType, ReleaseStatus, TermOfSupport, Verification},
+    Error,
+};
+
+/// Filters for the `packages` endpoint.
+///
+/// The list filters are sent as repeated query parameters, so e.g. two
+/// distributions select the packages of either one. Empty lists don't filter.
+#[derive(Debug, Clone, Default)]
 pub struct PackageQueryOptions {
     pub version: Option<String>,
-    pub distribution: Option<String>,
-    pub architecture: Option<String>,
-    pub archive_type: Option<String>,
-    pub package_type: Option<String>,
-    pub operating_system: Option<String>,
-    pub libc_type: Option<String>,
-    pub release_status: Option<String>,
-    pub term_of_support: Option<String>,
-    pub bitness: Option<String>,
+    /// The major version of the JDK a package is based on.
+    pub jdk_version: Option<u32>,
+    pub distribution: Vec<String>,
+    pub architecture: Vec<Architecture>,
+    pub fpu: Vec<Fpu>,
+    pub archive_type: Vec<ArchiveType>,
+    pub package_type: Vec<PackageType>,
+    pub operating_system: Vec<OperatingSystem>,
+    pub libc_type: Vec<LibCType>,
+    pub release_status: Vec<ReleaseStatus>,
+    pub term_of_support: Vec<TermOfSupport>,
+    pub bitness: Vec<Bitness>,
     pub javafx_bundled: Option<bool>,
     pub directly_downloadable: Option<bool>,
-    pub latest: Option<String>,
+    pub signature_available: Option<bool>,
+    pub free_to_use_in_production: Option<bool>,
+    pub tck_tested: Option<Verification>,
+    pub aqavit_certified: Option<Verification>,
+    /// Feature API strings, like `crac` or `leyden`.
+    pub feature: Vec<String>,
+    /// Discovery scopes, like `public` or `build_of_openjdk`.
+    pub discovery_scope_id: Vec<String>,
+    pub include_versions: Option<bool>,
+    pub latest: Option<Latest>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MajorVersionsQueryOptions {
     pub early_access: Option<bool>,
     pub general_availability: Option<bool>,
     pub maintained: Option<bool>,
 }
 
-impl std::fmt::Display for PackageQueryOptions {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut query_opts = vec![];
-
-        // Prepare your eyes.
-
-        if let Some(v) = &self.version {
-            query_opts.push(format!("version={}", v));
-        }
-
-        if let Some(v) = &self.distribution {
-            query_opts.push(format!("distribution={}", v));
-        }
-
-        if let Some(v) = &self.architecture {
-            query_opts.push(format!("architecture={}", v));
-        }
+/// Appends query pairs to a URL or string, skipping unset values.
+pub(crate) struct QueryBuilder<'s, 'a, T: form_urlencoded::Target>(&'s mut form_urlencoded::Serializer<'a, T>);
 
-        if let Some(v) = &self.archive_type {
-            query_opts.push(format!("archive_type={}", v));
-        }
-
-        if let Some(v) = &self.package_type {
-            query_opts.push(format!("package_type={}", v));
-        }
-
-        if let Some(v) = &self.operating_system {
-            query_opts.push(format!("operating_system={}", v));
-        }
-
-        if let Some(v) = &self.libc_type {
-            query_opts.push(format!("libc_type={}", v));
+impl<T: form_urlencoded::Target> QueryBuilder<'_, '_, T> {
+    fn opt(&mut self, key: &str, value: &Option<impl std::fmt::Display>) -> &mut Self {
+        if let Some(v) = value {
+            self.0.append_pair(key, &v.to_string());
         }
+        self
+    }
 
-        if let Some(v) = &self.release_status {
-            query_opts.push(format!("release_status={}", v));
+    fn all(&mut self, key: &str, values: &[impl std::fmt::Display]) -> &mut Self {
+        for v in values {
+            self.0.append_pair(key, &v.to_string());
         }
+        self
+    }
+}
 
-        if let Some(v) = &self.term_of_support {
-            query_opts.push(format!("term_of_support={}", v));
-        }
+/// Options that are sent as an endpoint's query string.
+pub(crate) trait QueryParams {
+    fn append_to<T: form_urlencoded::Target>(&self, query: &mut QueryBuilder<'_, '_, T>);
+}
 
-        if let Some(v) = &self.bitness {
-            query_opts.push(format!("bitness={}", v));
-        }
+impl QueryParams for PackageQueryOptions {
+    fn append_to<T: form_urlencoded::Target>(&self, query: &mut QueryBuilder<'_, '_, T>) {
+        query
+            .opt("version", &self.version)
+            .opt("jdk_version", &self.jdk_version)
+            .all("distribution", &self.distribution)
+            .all("architecture", &self.architecture)
+            .all("fpu", &self.fpu)
+            .all("archive_type", &self.archive_type)
+            .all("package_type", &self.package_type)
+            .all("operating_system", &self.operating_system)
+            .all("libc_type", &self.libc_type)
+            .all("release_status", &self.release_status)
+            .all("term_of_support", &self.term_of_support)
+            .all("bitness", &self.bitness)
+            .opt("javafx_bundled", &self.javafx_bundled)
+            .opt("directly_downloadable", &self.directly_downloadable)
+            .opt("signature_available", &self.signature_available)
+            .opt("free_to_use_in_production", &self.free_to_use_in_production)
+            .opt("tck_tested", &self.tck_tested)
+            .opt("aqavit_certified", &self.aqavit_certified)
+            .all("feature", &self.feature)
+            .all("discovery_scope_id", &self.discovery_scope_id)
+            .opt("include_versions", &self.include_versions)
+            .opt("latest", &self.latest);
+    }
+}
 
-        if let Some(v) = &self.javafx_bundled {
-            query_opts.push(format!("javafx_bundled={}", v));
-        }
+impl QueryParams for MajorVersionsQueryOptions {
+    fn append_to<T: form_urlencoded::Target>(&self, query: &mut QueryBuilder<'_, '_, T>) {
+        query
+            .opt("ea", &self.early_access)
+            .opt("ga", &self.general_availability)
+            .opt("maintained", &self.maintained);
+    }
+}
 
-        if let Some(v) = &self.directly_downloadable {
-            query_opts.push(format!("directly_downloadable={}", v));
+impl PackageQueryOptions {
+    /// Parses options back from a query string (with or without the leading
+    /// `?`) or from a full `packages` URL.
+    pub fn from_query(query: &str) -> Result<Self, Error> {
+        let query = match Url::parse(query) {
+            Ok(url) => url.query().unwrap_or_default().to_string(),
+            Err(_) => query.trim_start_matches('?').to_string(),
+        };
+
+        let mut opts = Self::default();
+
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            let value = value.into_owned();
+
+            match key.as_ref() {
+                "version" => opts.version = Some(value),
+                "jdk_version" => opts.jdk_version = Some(parse_value(&key, &value)?),
+                "distribution" => opts.distribution.push(value),
+                "architecture" => opts.architecture.push(value.into()),
+                "fpu" => opts.fpu.push(value.into()),
+                "archive_type" => opts.archive_type.push(value.into()),
+                "package_type" => opts.package_type.push(value.into()),
+                "operating_system" => opts.operating_system.push(value.into()),
+                "libc_type" | "lib_c_type" => opts.libc_type.push(value.into()),
+                "release_status" => opts.release_status.push(value.into()),
+                "term_of_support" => opts.term_of_support.push(value.into()),
+                "bitness" => opts.bitness.push(value.into()),
+                "javafx_bundled" => opts.javafx_bundled = Some(parse_value(&key, &value)?),
+                "directly_downloadable" => opts.directly_downloadable = Some(parse_value(&key, &value)?),
+                "signature_available" => opts.signature_available = Some(parse_value(&key, &value)?),
+                "free_to_use_in_production" => opts.free_to_use_in_production = Some(parse_value(&key, &value)?),
+                "tck_tested" => opts.tck_tested = Some(value.into()),
+                "aqavit_certified" => opts.aqavit_certified = Some(value.into()),
+                "feature" => opts.feature.push(value),
+                "discovery_scope_id" => opts.discovery_scope_id.push(value),
+                "include_versions" => opts.include_versions = Some(parse_value(&key, &value)?),
+                "latest" => opts.latest = Some(value.into()),
+                k => return Err(Error::InvalidQuery(format!("unknown parameter `{}`", k))),
+            }
         }
 
-        if let Some(v) = &self.latest {
-            query_opts.push(format!("latest={}", v));
-        }
+        Ok(opts)
+    }
+}
 
-        let mut used_init_char = false;
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidQuery(format!("invalid value `{}` for `{}`", value, key)))
+}
 
-        for o in query_opts {
-            // The query should start with "?", following options will use "&"
-            f.write_str(&format!("{}{}", if used_init_char { "&" } else { "" }, o)).unwrap();
+impl std::str::FromStr for PackageQueryOptions {
+    type Err = Error;
 
-            used_init_char = true;
-        }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_query(s)
+    }
+}
 
-        Ok(())
+/// Formats the options as an encoded query string, without the leading `?`.
+impl std::fmt::Display for PackageQueryOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&query_string(self))
     }
 }
 
+/// Formats the options as an encoded query string, without the leading `?`.
 impl std::fmt::Display for MajorVersionsQueryOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut query_opts = vec![];
-
-        if let Some(v) = &self.early_access {
-            query_opts.push(format!("ea={}", v));
-        }
-
-        if let Some(v) = &self.general_availability {
-            query_opts.push(format!("ga={}", v));
-        }
+        f.write_str(&query_string(self))
+    }
+}
 
-        if let Some(v) = &self.maintained {
-            query_opts.push(format!("maintained={}", v));
-        }
+fn query_string(params: &impl QueryParams) -> String {
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    params.append_to(&mut QueryBuilder(&mut serializer));
+    serializer.finish()
+}
 
-        let mut used_init_char = false;
+/// Builds `<base>/v3.0/<segments...>`, with `params` as the query.
+///
+/// Segments are percent-encoded, so ids can't change the path.
+pub(crate) fn endpoint_url(
+    base: impl std::fmt::Display,
+    segments: &[&str],
+    params: Option<&impl QueryParams>,
+) -> Result<String, Error> {
+    let mut query_url = Url::parse(base.to_string().as_str())
+        .map_err(Error::UrlParse)?;
 
-        for o in query_opts {
-            // The query should start with "?", following options will use "&"
-            f.write_str(&format!("{}{}", if used_init_char { "&" } else { "" }, o)).unwrap();
+    query_url
+        .path_segments_mut()
+        .map_err(|_| Error::UrlParse(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+        .pop_if_empty()
+        .push("v3.0")
+        .extend(segments);
 
-            used_init_char = true;
-        }
+    if let Some(params) = params {
+        params.append_to(&mut QueryBuilder(&mut query_url.query_pairs_mut()));
+    }
 
-        Ok(())
+    if query_url.query() == Some("") {
+        query_url.set_query(None);
     }
+
+    Ok(query_url.to_string())
 }
 
+/// For endpoints without query options.
+const NO_QUERY: Option<&PackageQueryOptions> = None;
+
 pub fn create_package_query_url(
     base: impl std::fmt::Display,
     options: Option<PackageQueryOptions>,
 ) -> Result<String, Error> {
-    let mut query_url = Url::parse(base.to_string().as_str())
-        .map_err(Error::UrlParse)?;
-
-    query_url = query_url.join("v3.0/").map_err(Error::UrlParse)?;
-    query_url = query_url.join("packages").map_err(Error::UrlParse)?;
+    endpoint_url(base, &["packages"], options.as_ref())
+}
 
-    let query_string = options.map(|o| o.to_string());
-    query_url.set_query(query_string.as_deref());
+pub fn create_jdks_query_url(
+    base: impl std::fmt::Display,
+    options: Option<PackageQueryOptions>,
+) -> Result<String, Error> {
+    endpoint_url(base, &["packages", "jdks"], options.as_ref())
+}
 
-    Ok(query_url.to_string())
+pub fn create_jres_query_url(
+    base: impl std::fmt::Display,
+    options: Option<PackageQueryOptions>,
+) -> Result<String, Error> {
+    endpoint_url(base, &["packages", "jres"], options.as_ref())
 }
 
 pub fn create_package_info_query_url(
     base: impl std::fmt::Display,
     package: String,
 ) -> Result<String, Error> {
-    let mut query_url = Url::parse(base.to_string().as_str())
-        .map_err(Error::UrlParse)?;
-
-    query_url = query_url.join("v3.0/").map_err(Error::UrlParse)?;
-    query_url = query_url.join("ids/").map_err(Error::UrlParse)?;
-    query_url = query_url.join(&package).map_err(Error::UrlParse)?;
-
-    Ok(query_url.to_string())
+    endpoint_url(base, &["ids", &package], NO_QUERY)
 }
 
 pub fn create_major_versions_query_url(
     base: impl std::fmt::Display,
     options: Option<MajorVersionsQueryOptions>,
 ) -> Result<String, Error> {
-    let mut query_url = Url::parse(base.to_string().as_str())
-        .map_err(Error::UrlParse)?;
+    endpoint_url(base, &["major_versions"], options.as_ref())
+}
 
-    query_url = query_url.join("v3.0/").map_err(Error::UrlParse)?;
-    query_url = query_url.join("major_versions").map_err(Error::UrlParse)?;
+pub fn create_package_redirect_url(
+    base: impl std::fmt::Display,
+    package: String,
+) -> Result<String, Error> {
+    endpoint_url(base, &["ids", &package, "redirect"], NO_QUERY)
+}
 
-    let query_string = options.map(|o| o.to_string());
-    query_url.set_query(query_string.as_deref());
+/// `major_versions/{n}`, or `major_versions/{n}/ga` and `/ea` with a release
+/// status.
+pub fn create_major_version_query_url(
+    base: impl std::fmt::Display,
+    major_version: u32,
+    release_status: Option<ReleaseStatus>,
+) -> Result<String, Error> {
+    let major_version = major_version.to_string();
 
-    Ok(query_url.to_string())
+    match release_status {
+        Some(r) => endpoint_url(base, &["major_versions", &major_version, r.as_str()], NO_QUERY),
+        None => endpoint_url(base, &["major_versions", &major_version], NO_QUERY),
+    }
 }
 
-pub fn create_distributions_query_url(
+pub fn create_named_major_versions_query_url(
     base: impl std::fmt::Display,
+    query: MajorVersionQuery,
 ) -> Result<String, Error> {
-    let mut query_url = Url::parse(base.to_string().as_str())
-        .map_err(Error::UrlParse)?;
-
-    query_url = query_url.join("v3.0/").map_err(Error::UrlParse)?;
-    query_url = query_url.join("distributions").map_err(Error::UrlParse)?;
+    endpoint_url(base, &["major_versions", query.as_str()], NO_QUERY)
+}
 
-    Ok(query_url.to_string())
+pub fn create_distributions_query_url(
+    base: impl std::fmt::Display,
+) -> Result<String, Error> {
+    endpoint_url(base, &["distributions"], NO_QUERY)
 }
 
 pub fn create_distribution_info_query_url(
     base: impl std::fmt::Display,
     distribution: String,
 ) -> Result<String, Error> {
-    let mut query_url = Url::parse(base.to_string().as_str())
-        .map_err(Error::UrlParse)?;
-
-    query_url = query_url.join("v3.0/").map_err(Error::UrlParse)?;
-    query_url = query_url.join("distributions/").map_err(Error::UrlParse)?;
-    query_url = query_url.join(&distribution).map_err(Error::UrlParse)?;
+    endpoint_url(base, &["distributions", &distribution], NO_QUERY)
+}
 
-    Ok(query_url.to_string())
+pub fn create_distributions_for_version_query_url(
+    base: impl std::fmt::Display,
+    version: String,
+) -> Result<String, Error> {
+    endpoint_url(base, &["distributions", "versions", &version], NO_QUERY)
 }
 
 #[cfg(test)]
@@ -208,27 +311,101 @@ mod tests {
             crate::http::API_DEFAULT_URL,
             Some(PackageQueryOptions {
                 version: Some("17".to_string()),
-                distribution: Some("corretto".to_string()),
-                architecture: Some("x86".to_string()),
-                archive_type: Some("tar.gz".to_string()),
-                bitness: Some("32".to_string()),
+                distribution: vec!["corretto".to_string()],
+                architecture: vec![Architecture::X86],
+                archive_type: vec![ArchiveType::TarGz],
+                bitness: vec![Bitness::Bit32],
                 directly_downloadable: Some(true),
                 javafx_bundled: Some(false),
-                latest: Some("per-distro".to_string()),
-                libc_type: Some("glibc".to_string()),
-                operating_system: Some("linux".to_string()),
-                package_type: Some("jdk".to_string()),
-                release_status: Some("ga".to_string()),
-                term_of_support: Some("lts".to_string())
+                latest: Some(Latest::PerDistro),
+                libc_type: vec![LibCType::Glibc],
+                operating_system: vec![OperatingSystem::Linux],
+                package_type: vec![PackageType::Jdk],
+                release_status: vec![ReleaseStatus::Ga],
+                term_of_support: vec![TermOfSupport::Lts],
+                ..Default::default()
             })
         ).unwrap();
 
         assert_eq!(
             query_url,
-            format!("{}v3.0/packages?version=17&distribution=corretto&architecture=x86&archive_type=tar.gz&package_type=jdk&operating_system=linux&libc_type=glibc&release_status=ga&term_of_support=lts&bitness=32&javafx_bundled=false&directly_downloadable=true&latest=per-distro", crate::http::API_DEFAULT_URL),
+            format!("{}v3.0/packages?version=17&distribution=corretto&architecture=x86&archive_type=tar.gz&package_type=jdk&operating_system=linux&libc_type=glibc&release_status=ga&term_of_support=lts&bitness=32&javafx_bundled=false&directly_downloadable=true&latest=per_distro", crate::http::API_DEFAULT_URL),
         )
     }
 
+    #[test]
+    fn create_package_query_url_repeated_test() {
+        let query_url = create_package_query_url(
+            crate::http::API_DEFAULT_URL,
+            Some(PackageQueryOptions {
+                distribution: vec!["temurin".to_string(), "zulu".to_string()],
+                architecture: vec![Architecture::X64, Architecture::Aarch64],
+                ..Default::default()
+            })
+        ).unwrap();
+
+        assert_eq!(
+            query_url,
+            format!("{}v3.0/packages?distribution=temurin&distribution=zulu&architecture=x64&architecture=aarch64", crate::http::API_DEFAULT_URL),
+        )
+    }
+
+    #[test]
+    fn create_package_query_url_certified_test() {
+        let query_url = create_package_query_url(
+            crate::http::API_DEFAULT_URL,
+            Some(PackageQueryOptions {
+                jdk_version: Some(21),
+                fpu: vec![Fpu::HardFloat],
+                signature_available: Some(true),
+                free_to_use_in_production: Some(true),
+                tck_tested: Some(Verification::Yes),
+                aqavit_certified: Some(Verification::Yes),
+                feature: vec!["crac".to_string()],
+                discovery_scope_id: vec!["public".to_string()],
+                include_versions: Some(true),
+                ..Default::default()
+            })
+        ).unwrap();
+
+        assert_eq!(
+            query_url,
+            format!("{}v3.0/packages?jdk_version=21&fpu=hard_float&signature_available=true&free_to_use_in_production=true&tck_tested=yes&aqavit_certified=yes&feature=crac&discovery_scope_id=public&include_versions=true", crate::http::API_DEFAULT_URL),
+        )
+    }
+
+    #[test]
+    fn query_encoding_test() {
+        let opts = PackageQueryOptions {
+            version: Some("17.0.9+9".to_string()),
+            distribution: vec!["a&b c".to_string()],
+            ..Default::default()
+        };
+
+        let query_url = create_package_query_url("https://example.com/disco", Some(opts.clone())).unwrap();
+        assert_eq!(query_url, "https://example.com/disco/v3.0/packages?version=17.0.9%2B9&distribution=a%26b+c");
+
+        let parsed: PackageQueryOptions = query_url.parse().unwrap();
+        assert_eq!(parsed.version, opts.version);
+        assert_eq!(parsed.distribution, opts.distribution);
+        assert_eq!(parsed.to_string(), opts.to_string());
+
+        let info_url = create_package_info_query_url("https://example.com/", "../x?y".to_string()).unwrap();
+        assert_eq!(info_url, "https://example.com/v3.0/ids/..%2Fx%3Fy");
+    }
+
+    #[test]
+    fn parse_package_query_test() {
+        let opts = PackageQueryOptions::from_query("?distribution=temurin&distribution=zulu&lib_c_type=musl&javafx_bundled=true").unwrap();
+
+        assert_eq!(opts.distribution, vec!["temurin", "zulu"]);
+        assert_eq!(opts.libc_type, vec![LibCType::Musl]);
+        assert_eq!(opts.javafx_bundled, Some(true));
+
+        assert!(matches!(PackageQueryOptions::from_query("javafx_bundled=maybe"), Err(Error::InvalidQuery(_))));
+        assert!(matches!(PackageQueryOptions::from_query("foo=bar"), Err(Error::InvalidQuery(_))));
+    }
+
     #[test]
     fn create_package_info_query_url_test() {
         let query_url = create_package_info_query_url(
@@ -259,6 +436,32 @@ mod tests {
         )
     }
 
+    #[test]
+    fn create_sub_resource_query_url_test() {
+        let base = crate::http::API_DEFAULT_URL;
+
+        assert_eq!(
+            create_major_version_query_url(base, 21, Some(ReleaseStatus::Ga)).unwrap(),
+            format!("{}v3.0/major_versions/21/ga", base),
+        );
+        assert_eq!(
+            create_named_major_versions_query_url(base, MajorVersionQuery::LatestLts).unwrap(),
+            format!("{}v3.0/major_versions/latest_lts", base),
+        );
+        assert_eq!(
+            create_jres_query_url(base, Some(PackageQueryOptions { version: Some("21".to_string()), ..Default::default() })).unwrap(),
+            format!("{}v3.0/packages/jres?version=21", base),
+        );
+        assert_eq!(
+            create_distributions_for_version_query_url(base, "17.0.9+9".to_string()).unwrap(),
+            format!("{}v3.0/distributions/versions/17.0.9+9", base),
+        );
+        assert_eq!(
+            create_package_redirect_url(base, "abc".to_string()).unwrap(),
+            format!("{}v3.0/ids/abc/redirect", base),
+        );
+    }
+
     #[test]
     fn create_distributions_query_url_test() {
         let query_url = create_distributions_query_url(
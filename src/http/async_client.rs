@@ -0,0 +1,245 @@
+use reqwest::{header::HeaderMap, Client, StatusCode};
+
+use crate::{
+    cache::{self, CacheEntry, Lookup, ResponseCache},
+    distributions::{DistributionInfo, DistributionList},
+    major_versions::{MajorVersionInfo, MajorVersionList},
+    package::{PackageInfo, PackageList},
+    types::{MajorVersionQuery, ReleaseStatus},
+    Error,
+};
+
+use super::{
+    api_url::{self, MajorVersionsQueryOptions, PackageQueryOptions},
+    response,
+    retry::{Failure, RetryPolicy},
+    DiscoClientBuilder,
+};
+
+/// Async counterpart of [`DiscoClient`](super::DiscoClient).
+///
+/// Built through [`DiscoClientBuilder::build_async`], it exposes the same
+/// operations and returns the same types and errors.
+#[derive(Debug, Clone)]
+pub struct AsyncDiscoClient {
+    client: Client,
+    redirect_client: Client,
+    base_url: String,
+    cache: Option<ResponseCache>,
+    retry_policy: RetryPolicy,
+}
+
+impl AsyncDiscoClient {
+    pub(super) fn new_with(
+        client: Client,
+        redirect_client: Client,
+        base_url: String,
+        cache: Option<ResponseCache>,
+        retry_policy: RetryPolicy,
+    ) -> Self {
+        Self { client, redirect_client, base_url, cache, retry_policy }
+    }
+
+    /// Creates a client with the default settings.
+    pub fn new() -> Result<Self, Error> {
+        DiscoClientBuilder::new().build_async()
+    }
+
+    pub fn builder() -> DiscoClientBuilder {
+        DiscoClientBuilder::new()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    pub async fn pull_packages(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_package_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_jdks(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_jdks_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_jres(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_jres_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_package_info(
+        &self,
+        package: String,
+    ) -> Result<PackageInfo, Error> {
+        let url = api_url::create_package_info_query_url(&self.base_url, package.clone())?;
+        let raw_list = self.get(&url).await.map_err(|e| e.for_resource("package", &package))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_major_versions(
+        &self,
+        query_opts: Option<MajorVersionsQueryOptions>,
+    ) -> Result<MajorVersionList, Error> {
+        let url = api_url::create_major_versions_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_major_version(
+        &self,
+        major_version: u32,
+        release_status: Option<ReleaseStatus>,
+    ) -> Result<MajorVersionInfo, Error> {
+        let url = api_url::create_major_version_query_url(&self.base_url, major_version, release_status)?;
+        let raw_list = self.get(&url).await.map_err(|e| e.for_resource("major version", &major_version.to_string()))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_named_major_versions(
+        &self,
+        query: MajorVersionQuery,
+    ) -> Result<MajorVersionList, Error> {
+        let url = api_url::create_named_major_versions_query_url(&self.base_url, query)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_distributions(&self) -> Result<DistributionList, Error> {
+        let url = api_url::create_distributions_query_url(&self.base_url)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_distribution_info(
+        &self,
+        distribution: String,
+    ) -> Result<DistributionInfo, Error> {
+        let url = api_url::create_distribution_info_query_url(&self.base_url, distribution.clone())?;
+        let raw_list = self.get(&url).await.map_err(|e| e.for_resource("distribution", &distribution))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn pull_distributions_for_version(
+        &self,
+        version: String,
+    ) -> Result<DistributionList, Error> {
+        let url = api_url::create_distributions_for_version_query_url(&self.base_url, version)?;
+        let raw_list = self.get(&url).await?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub async fn package_download_uri(&self, package: String) -> Result<String, Error> {
+        let url = api_url::create_package_redirect_url(&self.base_url, package.clone())?;
+
+        let r = self.redirect_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| response::transport_error(&url, e))?;
+
+        let status = r.status();
+        let headers = r.headers().clone();
+        let body = if status.is_redirection() { String::new() } else { r.text().await.unwrap_or_default() };
+
+        response::redirect_location(status, &url, &headers, &body).map_err(|e| e.for_resource("package", &package))
+    }
+
+    // The cache does small synchronous file operations, which is fine to do
+    // on a runtime thread
+    async fn get(&self, url: &str) -> Result<String, Error> {
+        let Some(cache) = &self.cache else {
+            return self.fetch(url, HeaderMap::new()).await.map(|(_, body)| body);
+        };
+
+        let stale = match cache.lookup(url) {
+            Lookup::Fresh(body) => return Ok(body),
+            Lookup::Stale(entry) => Some(entry),
+            Lookup::Miss => None,
+        };
+
+        let headers = stale.as_ref().map(CacheEntry::conditional_headers).unwrap_or_default();
+
+        match (self.fetch(url, headers).await, stale) {
+            (Ok((None, _)), Some(entry)) => Ok(cache.refresh(entry)),
+            (Ok((Some(headers), body)), _) => {
+                cache.store(url, &headers, &body);
+                Ok(body)
+            }
+            (Err(e), Some(entry)) if cache::is_network_failure(&e) => Ok(entry.body),
+            (Ok((None, _)), None) => Err(response::status_error(StatusCode::NOT_MODIFIED, url, &HeaderMap::new(), "")),
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Sends a GET request, retrying as the retry policy allows. `None`
+    /// headers mean the server answered `304 Not Modified`.
+    async fn fetch(&self, url: &str, headers: HeaderMap) -> Result<(Option<HeaderMap>, String), Error> {
+        let mut attempt = 1;
+
+        loop {
+            let failure = match self.fetch_once(url, headers.clone()).await {
+                Ok(r) => return Ok(r),
+                Err(f) => f,
+            };
+
+            match self.retry_policy.next_delay(attempt, &failure) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(self.retry_policy.give_up(attempt, failure.error)),
+            }
+
+            attempt += 1;
+        }
+    }
+
+    async fn fetch_once(&self, url: &str, headers: HeaderMap) -> Result<(Option<HeaderMap>, String), Failure> {
+        let r = self.client
+            .get(url)
+            .headers(headers)
+            .send()
+            .await
+            .map_err(|e| Failure::from_reqwest(url, e))?;
+
+        let status = r.status();
+
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok((None, String::new()));
+        }
+
+        let headers = r.headers().clone();
+
+        if !status.is_success() {
+            let body = r.text().await.unwrap_or_default();
+
+            return Err(Failure::from_status(status, &headers, response::status_error(status, url, &headers, &body)));
+        }
+
+        Ok((Some(headers), r.text().await.map_err(|e| Failure::from_reqwest(url, e))?))
+    }
+}
@@ -0,0 +1,519 @@
+use reqwest::{
+    blocking::Client,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    redirect::Policy,
+    Proxy,
+    StatusCode,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    cache::{self, CacheEntry, Lookup, ResponseCache},
+    checksum,
+    distributions::{DistributionInfo, DistributionList},
+    download::{self, DownloadProgress},
+    major_versions::{MajorVersionInfo, MajorVersionList},
+    package::{Package, PackageInfo, PackageInfoInner, PackageList},
+    types::{ChecksumType, MajorVersionQuery, ReleaseStatus},
+    Error,
+};
+
+use super::{
+    api_url::{self, MajorVersionsQueryOptions, PackageQueryOptions},
+    response,
+    retry::{Failure, RetryPolicy},
+    API_DEFAULT_URL,
+    CONNECT_TIMEOUT_MS,
+};
+
+// The blocking and async reqwest builders share method names but no trait
+macro_rules! configure_client {
+    ($builder:expr, $opts:expr) => {{
+        let mut builder = $builder
+            .user_agent($opts.user_agent.clone())
+            .default_headers($opts.default_headers.clone());
+
+        if let Some(t) = $opts.connect_timeout {
+            builder = builder.connect_timeout(t);
+        }
+
+        if let Some(t) = $opts.timeout {
+            builder = builder.timeout(t);
+        }
+
+        for p in &$opts.proxies {
+            builder = builder.proxy(p.clone());
+        }
+
+        if $opts.no_proxy {
+            builder = builder.no_proxy();
+        }
+
+        builder
+    }};
+}
+
+/// A client for the foojay Disco API.
+///
+/// The client owns a single pooled HTTP client, so connections (and TLS
+/// sessions) are reused across requests. Cloning it is cheap.
+#[derive(Debug, Clone)]
+pub struct DiscoClient {
+    client: Client,
+    /// Doesn't follow redirects, for `ids/{id}/redirect`.
+    redirect_client: Client,
+    base_url: String,
+    cache: Option<ResponseCache>,
+    retry_policy: RetryPolicy,
+}
+
+/// Builder for [`DiscoClient`].
+#[derive(Debug)]
+pub struct DiscoClientBuilder {
+    base_url: String,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: String,
+    default_headers: HeaderMap,
+    proxies: Vec<Proxy>,
+    no_proxy: bool,
+    cache: Option<ResponseCache>,
+    retry_policy: RetryPolicy,
+}
+
+impl Default for DiscoClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: API_DEFAULT_URL.to_string(),
+            connect_timeout: Some(Duration::from_millis(CONNECT_TIMEOUT_MS as u64)),
+            timeout: None,
+            user_agent: format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            default_headers: HeaderMap::new(),
+            proxies: vec![],
+            no_proxy: false,
+            cache: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
+
+impl DiscoClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Base URL of the Disco API, defaults to `https://api.foojay.io/disco/`.
+    pub fn base_url(mut self, base_url: impl std::fmt::Display) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Timeout for establishing a connection, `None` disables it.
+    pub fn connect_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Timeout for a whole request, `None` (the default) disables it.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl std::fmt::Display) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Adds a header that is sent with every request.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Replaces all default headers.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Adds a proxy. Can be called multiple times.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Disables all proxies, including the system ones.
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Caches API responses on disk, see [`ResponseCache`]. Off by default.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// How failed requests are retried, see [`RetryPolicy`]. Defaults to a
+    /// single attempt.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn build(self) -> Result<DiscoClient, Error> {
+        // Validate early, so a bad base URL fails here instead of on every request
+        url::Url::parse(&self.base_url).map_err(Error::UrlParse)?;
+
+        // The blocking client defaults to a 30s timeout, ours is no timeout
+        let client = configure_client!(Client::builder().timeout(None), self)
+            .build()
+            .map_err(Error::Http)?;
+
+        let redirect_client = configure_client!(Client::builder().timeout(None), self)
+            .redirect(Policy::none())
+            .build()
+            .map_err(Error::Http)?;
+
+        Ok(DiscoClient {
+            client,
+            redirect_client,
+            base_url: self.base_url,
+            cache: self.cache,
+            retry_policy: self.retry_policy,
+        })
+    }
+
+    /// Builds an [`AsyncDiscoClient`](super::AsyncDiscoClient) with the same settings.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<super::AsyncDiscoClient, Error> {
+        url::Url::parse(&self.base_url).map_err(Error::UrlParse)?;
+
+        let client = configure_client!(reqwest::Client::builder(), self)
+            .build()
+            .map_err(Error::Http)?;
+
+        let redirect_client = configure_client!(reqwest::Client::builder(), self)
+            .redirect(Policy::none())
+            .build()
+            .map_err(Error::Http)?;
+
+        Ok(super::AsyncDiscoClient::new_with(client, redirect_client, self.base_url, self.cache, self.retry_policy))
+    }
+}
+
+impl DiscoClient {
+    /// Creates a client with the default settings.
+    pub fn new() -> Result<Self, Error> {
+        DiscoClientBuilder::new().build()
+    }
+
+    pub fn builder() -> DiscoClientBuilder {
+        DiscoClientBuilder::new()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    pub fn pull_packages(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_package_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// Like [`pull_packages`](Self::pull_packages), limited to JDKs.
+    pub fn pull_jdks(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_jdks_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// Like [`pull_packages`](Self::pull_packages), limited to JREs.
+    pub fn pull_jres(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let url = api_url::create_jres_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub fn pull_package_info(
+        &self,
+        package: String,
+    ) -> Result<PackageInfo, Error> {
+        let url = api_url::create_package_info_query_url(&self.base_url, package.clone())?;
+        let raw_list = self.get(&url).map_err(|e| e.for_resource("package", &package))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub fn pull_major_versions(
+        &self,
+        query_opts: Option<MajorVersionsQueryOptions>,
+    ) -> Result<MajorVersionList, Error> {
+        let url = api_url::create_major_versions_query_url(&self.base_url, query_opts)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// A single major version. With a release status, only its GA or EA
+    /// versions are listed.
+    pub fn pull_major_version(
+        &self,
+        major_version: u32,
+        release_status: Option<ReleaseStatus>,
+    ) -> Result<MajorVersionInfo, Error> {
+        let url = api_url::create_major_version_query_url(&self.base_url, major_version, release_status)?;
+        let raw_list = self.get(&url).map_err(|e| e.for_resource("major version", &major_version.to_string()))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// The major versions selected by a named query, like `latest_lts`.
+    pub fn pull_named_major_versions(
+        &self,
+        query: MajorVersionQuery,
+    ) -> Result<MajorVersionList, Error> {
+        let url = api_url::create_named_major_versions_query_url(&self.base_url, query)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub fn pull_distributions(&self) -> Result<DistributionList, Error> {
+        let url = api_url::create_distributions_query_url(&self.base_url)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    pub fn pull_distribution_info(
+        &self,
+        distribution: String,
+    ) -> Result<DistributionInfo, Error> {
+        let url = api_url::create_distribution_info_query_url(&self.base_url, distribution.clone())?;
+        let raw_list = self.get(&url).map_err(|e| e.for_resource("distribution", &distribution))?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// The distributions that offer `version`.
+    pub fn pull_distributions_for_version(
+        &self,
+        version: String,
+    ) -> Result<DistributionList, Error> {
+        let url = api_url::create_distributions_for_version_query_url(&self.base_url, version)?;
+        let raw_list = self.get(&url)?;
+
+        response::decode(&url, &raw_list)
+    }
+
+    /// Resolves the download URI of a package through `ids/{id}/redirect`,
+    /// without downloading it.
+    pub fn package_download_uri(&self, package: String) -> Result<String, Error> {
+        let url = api_url::create_package_redirect_url(&self.base_url, package.clone())?;
+
+        let r = self.redirect_client
+            .get(&url)
+            .send()
+            .map_err(|e| response::transport_error(&url, e))?;
+
+        let status = r.status();
+        let headers = r.headers().clone();
+        let body = if status.is_redirection() { String::new() } else { r.text().unwrap_or_default() };
+
+        response::redirect_location(status, &url, &headers, &body).map_err(|e| e.for_resource("package", &package))
+    }
+
+    /// Downloads `package` into `dir`, named after its `filename`.
+    ///
+    /// The archive is streamed to disk, `progress` is called after every chunk
+    /// with `Package::size` as the expected length. Returns the path of the
+    /// downloaded file.
+    pub fn download_package(
+        &self,
+        package: &Package,
+        dir: impl AsRef<Path>,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, Error> {
+        let info = self.pull_package_info(package.id.clone())?;
+
+        self.download_package_info(&info.result[0], dir, u64::try_from(package.size).ok().filter(|s| *s > 0), progress)
+    }
+
+    /// Same as [`download_package`](Self::download_package), by package id.
+    ///
+    /// The expected length is taken from the `Content-Length` header.
+    pub fn download_package_id(
+        &self,
+        package: String,
+        dir: impl AsRef<Path>,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, Error> {
+        let info = self.pull_package_info(package)?;
+
+        self.download_package_info(&info.result[0], dir, None, progress)
+    }
+
+    /// Verifies a downloaded file against the checksum of its package.
+    ///
+    /// Fails with [`Error::ChecksumMismatch`] if the file doesn't match.
+    pub fn verify_package_file(
+        &self,
+        path: impl AsRef<Path>,
+        info: &PackageInfoInner,
+    ) -> Result<(), Error> {
+        let (checksum_type, expected) = self.package_checksum(info)?;
+
+        checksum::verify_checksum_file(path, &checksum_type, &expected)
+    }
+
+    /// The expected checksum of a package and its type.
+    ///
+    /// Uses `checksum` if set, otherwise fetches and parses `checksum_uri`.
+    /// A missing type is inferred from the checksum's length.
+    pub fn package_checksum(&self, info: &PackageInfoInner) -> Result<(ChecksumType, String), Error> {
+        let expected = match info.checksum.trim() {
+            "" if info.checksum_uri.is_empty() => {
+                return Err(Error::UnsupportedChecksum(info.filename.clone()));
+            }
+            "" => checksum::parse_checksum_file(&self.get(&info.checksum_uri)?, &info.filename)
+                .ok_or_else(|| Error::UnsupportedChecksum(info.checksum_uri.clone()))?,
+            c => c.to_string(),
+        };
+
+        let checksum_type = match &info.checksum_type {
+            ChecksumType::Other(_) => checksum::infer_checksum_type(&expected)
+                .ok_or_else(|| Error::UnsupportedChecksum(info.checksum_type.to_string()))?,
+            t => t.clone(),
+        };
+
+        Ok((checksum_type, expected))
+    }
+
+    /// Verifies a downloaded file against the detached signature at
+    /// `signature_uri`, using the keys `keyring` trusts for `distribution`.
+    ///
+    /// Fails with [`Error::SignatureUnavailable`] if the package isn't signed.
+    #[cfg(feature = "pgp")]
+    pub fn verify_package_signature(
+        &self,
+        path: impl AsRef<Path>,
+        info: &PackageInfoInner,
+        distribution: &str,
+        keyring: &crate::signature::Keyring,
+    ) -> Result<(), Error> {
+        if info.signature_uri.trim().is_empty() {
+            return Err(Error::SignatureUnavailable(info.filename.clone()));
+        }
+
+        let signature = response::send(&self.client, &info.signature_uri)?
+            .bytes()
+            .map_err(|e| response::transport_error(&info.signature_uri, e))?;
+
+        keyring.verify(path, &signature, Some(distribution))
+    }
+
+    pub(crate) fn download_package_info(
+        &self,
+        info: &PackageInfoInner,
+        dir: impl AsRef<Path>,
+        expected_len: Option<u64>,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, Error> {
+        let path = download::target_path(dir.as_ref(), &info.filename)?;
+
+        download::download_to(&self.client, &info.direct_download_uri, &path, expected_len, progress)?;
+
+        Ok(path)
+    }
+
+    fn get(&self, url: &str) -> Result<String, Error> {
+        let Some(cache) = &self.cache else {
+            return self.fetch(url, HeaderMap::new()).map(|(_, body)| body);
+        };
+
+        let stale = match cache.lookup(url) {
+            Lookup::Fresh(body) => return Ok(body),
+            Lookup::Stale(entry) => Some(entry),
+            Lookup::Miss => None,
+        };
+
+        let headers = stale.as_ref().map(CacheEntry::conditional_headers).unwrap_or_default();
+
+        match (self.fetch(url, headers), stale) {
+            (Ok((None, _)), Some(entry)) => Ok(cache.refresh(entry)),
+            (Ok((Some(headers), body)), _) => {
+                cache.store(url, &headers, &body);
+                Ok(body)
+            }
+            (Err(e), Some(entry)) if cache::is_network_failure(&e) => Ok(entry.body),
+            (Ok((None, _)), None) => Err(response::status_error(StatusCode::NOT_MODIFIED, url, &HeaderMap::new(), "")),
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Sends a GET request, retrying as the retry policy allows. `None`
+    /// headers mean the server answered `304 Not Modified`.
+    fn fetch(&self, url: &str, headers: HeaderMap) -> Result<(Option<HeaderMap>, String), Error> {
+        let mut attempt = 1;
+
+        loop {
+            let failure = match self.fetch_once(url, headers.clone()) {
+                Ok(r) => return Ok(r),
+                Err(f) => f,
+            };
+
+            match self.retry_policy.next_delay(attempt, &failure) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return Err(self.retry_policy.give_up(attempt, failure.error)),
+            }
+
+            attempt += 1;
+        }
+    }
+
+    fn fetch_once(&self, url: &str, headers: HeaderMap) -> Result<(Option<HeaderMap>, String), Failure> {
+        let r = self.client
+            .get(url)
+            .headers(headers)
+            .send()
+            .map_err(|e| Failure::from_reqwest(url, e))?;
+
+        let status = r.status();
+
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok((None, String::new()));
+        }
+
+        let headers = r.headers().clone();
+
+        if !status.is_success() {
+            let body = r.text().unwrap_or_default();
+
+            return Err(Failure::from_status(status, &headers, response::status_error(status, url, &headers, &body)));
+        }
+
+        Ok((Some(headers), r.text().map_err(|e| Failure::from_reqwest(url, e))?))
+    }
+}
@@ -0,0 +1,185 @@
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::Error;
+
+use super::retry;
+
+const EXCERPT_LEN: usize = 200;
+
+/// Maps a transport error to the matching [`Error`] variant.
+pub(crate) fn transport_error(url: &str, error: reqwest::Error) -> Error {
+    if error.is_timeout() {
+        Error::Timeout { url: url.to_string() }
+    } else if error.is_connect() {
+        Error::Connect {
+            url: url.to_string(),
+            source: error,
+        }
+    } else {
+        Error::Http(error)
+    }
+}
+
+/// Sends a blocking GET request, failing on unsuccessful responses.
+pub(crate) fn send(client: &reqwest::blocking::Client, url: &str) -> Result<reqwest::blocking::Response, Error> {
+    let r = client
+        .get(url)
+        .send()
+        .map_err(|e| transport_error(url, e))?;
+
+    let status = r.status();
+
+    if status.is_success() {
+        return Ok(r);
+    }
+
+    let headers = r.headers().clone();
+    let body = r.text().unwrap_or_default();
+
+    Err(status_error(status, url, &headers, &body))
+}
+
+/// The resolved `Location` of a redirect response.
+///
+/// Any other response fails, a successful one as an [`Error::Status`] since
+/// the server was expected to redirect.
+pub(crate) fn redirect_location(status: StatusCode, url: &str, headers: &HeaderMap, body: &str) -> Result<String, Error> {
+    let location = headers
+        .get(reqwest::header::LOCATION)
+        .and_then(|l| l.to_str().ok())
+        .filter(|_| status.is_redirection());
+
+    match location {
+        Some(l) => url::Url::parse(url)
+            .and_then(|u| u.join(l))
+            .map(String::from)
+            .map_err(Error::UrlParse),
+        None if status.is_success() => Err(Error::Status {
+            code: status.as_u16(),
+            url: url.to_string(),
+            body_excerpt: excerpt(body, 0),
+            message: Some("expected a redirect".to_string()),
+        }),
+        None => Err(status_error(status, url, headers, body)),
+    }
+}
+
+/// Maps an unsuccessful response to the matching [`Error`] variant, keeping
+/// the API's own `message` if the body has one.
+pub(crate) fn status_error(status: StatusCode, url: &str, headers: &HeaderMap, body: &str) -> Error {
+    let message = api_message(body);
+
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => Error::RateLimited {
+            url: url.to_string(),
+            retry_after: retry::parse_retry_after(headers),
+            message,
+        },
+        _ => Error::Status {
+            code: status.as_u16(),
+            url: url.to_string(),
+            body_excerpt: excerpt(body, 0),
+            message,
+        },
+    }
+}
+
+/// Decodes a response body, failing with the endpoint and the part of the
+/// body that didn't parse.
+pub(crate) fn decode<T: DeserializeOwned>(url: &str, body: &str) -> Result<T, Error> {
+    serde_json::from_str(body).map_err(|e| json_error(url, body, e))
+}
+
+pub(crate) fn json_error(endpoint: &str, body: &str, error: serde_json::Error) -> Error {
+    // serde_json reports 1-based lines and columns
+    let offset = body
+        .split_inclusive('\n')
+        .take(error.line().saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>()
+        + error.column().saturating_sub(1);
+
+    Error::JsonParse {
+        endpoint: endpoint.to_string(),
+        snippet: excerpt(body, offset.saturating_sub(EXCERPT_LEN / 2)),
+        source: error,
+    }
+}
+
+/// The `message` field of the API's JSON envelope, if present and not empty.
+fn api_message(body: &str) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        message: Option<String>,
+    }
+
+    serde_json::from_str::<Envelope>(body)
+        .ok()
+        .and_then(|e| e.message)
+        .filter(|m| !m.trim().is_empty())
+}
+
+/// Up to `EXCERPT_LEN` bytes of `s` starting around `start`, on char boundaries.
+fn excerpt(s: &str, start: usize) -> String {
+    let floor = |mut i: usize| {
+        i = i.min(s.len());
+        while !s.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+
+    let start = floor(start);
+    let end = floor(start + EXCERPT_LEN);
+
+    s[start..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_error_test() {
+        let error = status_error(
+            StatusCode::NOT_FOUND,
+            "https://api.foojay.io/disco/v3.0/ids/abc",
+            &HeaderMap::new(),
+            r#"{"result":[],"message":"Package with id abc not found"}"#,
+        );
+
+        assert!(matches!(
+            error.for_resource("package", "abc"),
+            Error::NotFound { resource: "package", id, message: Some(m) } if id == "abc" && m.contains("not found"),
+        ));
+
+        let error = status_error(StatusCode::BAD_GATEWAY, "https://example.com", &HeaderMap::new(), "<html>");
+        assert!(matches!(error, Error::Status { code: 502, message: None, ref body_excerpt, .. } if body_excerpt == "<html>"));
+    }
+
+    #[test]
+    fn redirect_location_test() {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::LOCATION, "/files/jdk.tar.gz".parse().unwrap());
+
+        let location = redirect_location(StatusCode::FOUND, "https://example.com/v3.0/ids/a/redirect", &headers, "").unwrap();
+        assert_eq!(location, "https://example.com/files/jdk.tar.gz");
+
+        let error = redirect_location(StatusCode::OK, "https://example.com", &HeaderMap::new(), "").unwrap_err();
+        assert!(matches!(error, Error::Status { code: 200, .. }));
+    }
+
+    #[test]
+    fn json_error_test() {
+        let body = "{\n  \"message\": \"\",\n  \"result\": \"seventeen\"\n}";
+        let error = decode::<crate::MajorVersionList>("https://example.com/major_versions", body).unwrap_err();
+
+        let Error::JsonParse { endpoint, snippet, .. } = error else {
+            panic!("expected a JSON parse error");
+        };
+
+        assert_eq!(endpoint, "https://example.com/major_versions");
+        assert!(snippet.contains("seventeen"));
+    }
+}
@@ -0,0 +1,212 @@
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+use crate::Error;
+
+/// When and how often failed requests are retried.
+///
+/// Only transient failures are retried: connection errors, timeouts, `429 Too
+/// Many Requests` and `5xx` responses. Every API request is a GET, so retrying
+/// is always safe. The delay grows exponentially with jitter, a `Retry-After`
+/// header on `429`/`503` is honored up to `max_backoff`; if the server asks
+/// for a longer wait, the request fails right away.
+///
+/// The default policy makes a single attempt.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+/// A failed attempt.
+pub(crate) struct Failure {
+    pub error: Error,
+    pub transient: bool,
+    pub retry_after: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// A policy with exponential backoff making up to `max_attempts` attempts.
+    pub fn new(max_attempts: u32) -> Self {
+        Self::default().max_attempts(max_attempts)
+    }
+
+    /// Total attempts including the first one, at least 1.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Delay before the first retry, defaults to 500ms.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper limit for a single delay, defaults to 30s.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Factor the delay grows by after every retry, defaults to 2.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Randomizes each delay between half and the full value, on by default.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The delay before the next attempt, `None` if the request shouldn't be
+    /// retried after `attempt` attempts.
+    pub(crate) fn next_delay(&self, attempt: u32, failure: &Failure) -> Option<Duration> {
+        if !failure.transient || attempt >= self.max_attempts {
+            return None;
+        }
+
+        if let Some(retry_after) = failure.retry_after {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+
+        let exp = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let delay = self.initial_backoff.mul_f64(exp).min(self.max_backoff);
+
+        if self.jitter {
+            let half = delay / 2;
+            Some(half + half.mul_f64(random_fraction()))
+        } else {
+            Some(delay)
+        }
+    }
+
+    /// The error to return after giving up.
+    pub(crate) fn give_up(&self, attempts: u32, error: Error) -> Error {
+        if attempts > 1 {
+            Error::RetriesExhausted {
+                attempts,
+                source: Box::new(error),
+            }
+        } else {
+            error
+        }
+    }
+}
+
+impl Failure {
+    pub(crate) fn from_reqwest(url: &str, error: reqwest::Error) -> Self {
+        Self {
+            transient: error.is_connect() || error.is_timeout(),
+            retry_after: None,
+            error: super::response::transport_error(url, error),
+        }
+    }
+
+    pub(crate) fn from_status(status: StatusCode, headers: &HeaderMap, error: Error) -> Self {
+        let retry_after = match status {
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => parse_retry_after(headers),
+            _ => None,
+        };
+
+        Self {
+            transient: status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            retry_after,
+            error,
+        }
+    }
+}
+
+/// Parses `Retry-After` as seconds or HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = httpdate::parse_http_date(value).ok()?;
+
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// A random number in `[0, 1)`, good enough for jitter.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos());
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transient() -> Failure {
+        Failure {
+            error: Error::Timeout { url: String::new() },
+            transient: true,
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn retry_policy_delay_test() {
+        let policy = RetryPolicy::new(4)
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(3))
+            .jitter(false);
+
+        assert_eq!(policy.next_delay(1, &transient()), Some(Duration::from_secs(1)));
+        assert_eq!(policy.next_delay(2, &transient()), Some(Duration::from_secs(2)));
+        assert_eq!(policy.next_delay(3, &transient()), Some(Duration::from_secs(3)));
+        assert_eq!(policy.next_delay(4, &transient()), None);
+
+        let permanent = Failure { transient: false, ..transient() };
+        assert_eq!(policy.next_delay(1, &permanent), None);
+
+        let retry_after = Failure { retry_after: Some(Duration::from_secs(2)), ..transient() };
+        assert_eq!(policy.next_delay(1, &retry_after), Some(Duration::from_secs(2)));
+
+        let too_long = Failure { retry_after: Some(Duration::from_secs(60)), ..transient() };
+        assert_eq!(policy.next_delay(1, &too_long), None);
+
+        let jittered = RetryPolicy::new(2).next_delay(1, &transient()).unwrap();
+        assert!(jittered >= Duration::from_millis(250) && jittered <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn parse_retry_after_test() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+}
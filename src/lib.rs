@@ -1,7 +1,27 @@
+mod audit;
+mod cache;
+mod checksum;
+mod dirs;
+mod discover;
 mod distributions;
+mod download;
+mod extract;
 mod http;
+mod lock;
 mod major_versions;
+mod offline;
 mod package;
+mod platform;
+mod project;
+mod resolve;
+mod schema;
+#[cfg(feature = "pgp")]
+mod signature;
+mod store;
+#[cfg(test)]
+mod test_support;
+mod types;
+mod version;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -9,21 +29,143 @@ pub enum Error {
     UrlParse(url::ParseError),
     #[error("HTTP error: {0}")]
     Http(reqwest::Error),
-    #[error("HTTP response error: {0}")]
-    HttpResponse(String),
-    #[error("JSON parse error: {0}")]
-    JsonParse(serde_json::Error),
+    #[error("Could not connect to {url}: {source}")]
+    Connect { url: String, source: reqwest::Error },
+    #[error("Request to {url} timed out")]
+    Timeout { url: String },
+    #[error("HTTP {code} from {url}{}", api_message(.message))]
+    Status {
+        code: u16,
+        url: String,
+        /// The start of the response body.
+        body_excerpt: String,
+        /// The `message` of the API's JSON envelope.
+        message: Option<String>,
+    },
+    #[error("Rate limited by {url}{}", api_message(.message))]
+    RateLimited {
+        url: String,
+        retry_after: Option<std::time::Duration>,
+        message: Option<String>,
+    },
+    #[error("Giving up after {attempts} attempts: {source}")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+    #[error("JSON parse error at {endpoint}: {source}")]
+    JsonParse {
+        /// The URL or file the JSON came from.
+        endpoint: String,
+        /// The part of the JSON around the error.
+        snippet: String,
+        source: serde_json::Error,
+    },
+    #[error("Unknown {resource}: {id}{}", api_message(.message))]
+    NotFound {
+        resource: &'static str,
+        id: String,
+        message: Option<String>,
+    },
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("Invalid Java version: {0}")]
+    VersionParse(String),
+    #[error("IO error: {0}")]
+    Io(std::io::Error),
+    #[error("Invalid filename: {0}")]
+    InvalidFilename(String),
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Unsupported or missing checksum: {0}")]
+    UnsupportedChecksum(String),
+    #[error("Zip error: {0}")]
+    Zip(zip::result::ZipError),
+    #[error("Unsupported archive type: {0}")]
+    UnsupportedArchive(String),
+    #[error("Unsafe archive entry: {0}")]
+    UnsafeArchiveEntry(String),
+    #[error("No Java home found in {}", .0.display())]
+    JavaHomeNotFound(std::path::PathBuf),
+    #[error("Unsupported lockfile format version {0}")]
+    UnsupportedLockfile(u32),
+    #[error("No package matches {0}")]
+    NoMatchingPackage(String),
+    #[error("Invalid version file {}: {message}", .path.display())]
+    InvalidVersionFile { path: std::path::PathBuf, message: String },
+    #[error("Install directory {name} already holds package {package_id}")]
+    InstallConflict { name: String, package_id: String },
+    #[error("Unsupported platform: {0}")]
+    UnsupportedPlatform(String),
+    #[error("No signature available for {0}")]
+    SignatureUnavailable(String),
+    #[error("Signature verification failed: {0}")]
+    SignatureInvalid(String),
+    #[error("No signature keys trusted for distribution {0}")]
+    NoSignatureKeys(String),
+}
+
+fn api_message(message: &Option<String>) -> String {
+    message.as_ref().map(|m| format!(" ({})", m)).unwrap_or_default()
+}
+
+impl Error {
+    /// Turns a `404` into [`Error::NotFound`] for the requested resource.
+    pub(crate) fn for_resource(self, resource: &'static str, id: &str) -> Self {
+        match self {
+            Error::Status { code: 404, message, .. } => Error::NotFound {
+                resource,
+                id: id.to_string(),
+                message,
+            },
+            e => e,
+        }
+    }
 }
 
 pub use http::{
+    DiscoClient,
+    DiscoClientBuilder,
     PackageQueryOptions,
+    RetryPolicy,
     MajorVersionsQueryOptions,
-    pull_distribution_info,
-    pull_distributions,
-    pull_major_versions,
-    pull_packages,
-    pull_package_info,
 };
+#[cfg(feature = "async")]
+pub use http::AsyncDiscoClient;
+pub use audit::{AuditEntry, AuditReport, AuditStatus, AuditTarget};
+pub use cache::{CacheEntryInfo, ResponseCache};
+pub use checksum::{
+    compute_checksum,
+    infer_checksum_type,
+    parse_checksum_file,
+    verify_checksum,
+    verify_checksum_file,
+};
+pub use discover::{default_locations, discover_jdks, discover_jdks_in, JdkSource, ReleaseInfo, SystemJdk};
 pub use distributions::{DistributionList, DistributionInfo, Distribution};
-pub use major_versions::{MajorVersionList, MajorVersion};
+pub use download::DownloadProgress;
+pub use extract::{extract_archive, find_java_home};
+pub use lock::{LockChange, LockEntry, LockMismatch, Lockfile};
+pub use major_versions::{MajorVersionList, MajorVersionInfo, MajorVersion};
+pub use offline::CatalogSnapshot;
 pub use package::{PackageList, Package, Feature, PackageInfo, PackageInfoInner};
+pub use platform::Platform;
+pub use project::{sdkman_distribution, ProjectVersion, VersionFileKind};
+pub use resolve::{DefaultRanking, JdkSpec, RankingPolicy, Resolution, Resolver};
+pub use schema::{DecodeWarning, SchemaReport};
+#[cfg(feature = "pgp")]
+pub use signature::Keyring;
+pub use store::{Installation, JdkStore};
+pub use types::{
+    Architecture,
+    ArchiveType,
+    Bitness,
+    ChecksumType,
+    Fpu,
+    LibCType,
+    Latest,
+    MajorVersionQuery,
+    OperatingSystem,
+    PackageType,
+    ReleaseStatus,
+    TermOfSupport,
+    Verification,
+};
+pub use version::{JavaVersion, VersionReq};
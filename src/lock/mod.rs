@@ -0,0 +1,310 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    checksum,
+    download::DownloadProgress,
+    http::{response, DiscoClient},
+    package::PackageInfoInner,
+    platform::Platform,
+    resolve::{JdkSpec, RankingPolicy, Resolver},
+    types::{ArchiveType, ChecksumType, PackageType},
+    version::{JavaVersion, VersionReq},
+    Error,
+};
+
+const FORMAT_VERSION: u32 = 1;
+
+/// Pins the packages a project uses, so every machine installs the same
+/// bytes.
+///
+/// Each entry records a requirement, the platform it was resolved for and
+/// the exact package that was chosen. Downloading a locked entry never
+/// resolves again, it fetches the recorded URI and checks the recorded
+/// checksum. Stored as pretty-printed JSON.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Lockfile {
+    pub format_version: u32,
+    pub entries: Vec<LockEntry>,
+}
+
+/// A resolved requirement, see [`Lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockEntry {
+    /// Name of the requirement, like `default` or `build`. Unique per
+    /// platform.
+    pub name: String,
+    pub version: VersionReq,
+    pub package_type: PackageType,
+    pub distributions: Vec<String>,
+    pub archive_types: Vec<ArchiveType>,
+    pub javafx: bool,
+    pub platform: Platform,
+
+    pub package_id: String,
+    pub distribution: String,
+    pub java_version: JavaVersion,
+    pub archive_type: ArchiveType,
+    pub filename: String,
+    pub direct_download_uri: String,
+    pub checksum: String,
+    pub checksum_type: ChecksumType,
+}
+
+/// An entry whose package changed in [`Lockfile::update`].
+#[derive(Debug, Clone)]
+pub struct LockChange {
+    pub old: LockEntry,
+    pub new: LockEntry,
+}
+
+/// A locked value that no longer matches the catalog, found by
+/// [`Lockfile::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockMismatch {
+    pub name: String,
+    pub platform: Platform,
+    pub field: &'static str,
+    pub locked: String,
+    /// Empty if the package is gone.
+    pub current: String,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            entries: vec![],
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(Error::Io)?;
+        let lockfile: Self = response::decode(&path.as_ref().display().to_string(), &json)?;
+
+        if lockfile.format_version > FORMAT_VERSION {
+            return Err(Error::UnsupportedLockfile(lockfile.format_version));
+        }
+
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut json = serde_json::to_string_pretty(self)
+            .map_err(|e| response::json_error(&path.as_ref().display().to_string(), "", e))?;
+        json.push('\n');
+
+        std::fs::write(path, json).map_err(Error::Io)
+    }
+
+    /// The entry for `name` on `platform`.
+    pub fn entry(&self, name: &str, platform: &Platform) -> Option<&LockEntry> {
+        self.entries.iter().find(|e| e.name == name && e.platform == *platform)
+    }
+
+    /// Resolves `spec` and records the result as `name`, replacing an
+    /// existing entry for the same name and platform.
+    pub fn lock<P: RankingPolicy>(
+        &mut self,
+        client: &DiscoClient,
+        resolver: &Resolver<P>,
+        name: impl Into<String>,
+        spec: &JdkSpec,
+    ) -> Result<&LockEntry, Error> {
+        let entry = LockEntry::resolve(client, resolver, name.into(), spec)?;
+
+        let index = match self.entries.iter().position(|e| e.name == entry.name && e.platform == entry.platform) {
+            Some(i) => {
+                self.entries[i] = entry;
+                i
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+
+        Ok(&self.entries[index])
+    }
+
+    /// Resolves every entry again with its recorded requirement, returning
+    /// the entries whose package changed.
+    pub fn update<P: RankingPolicy>(
+        &mut self,
+        client: &DiscoClient,
+        resolver: &Resolver<P>,
+    ) -> Result<Vec<LockChange>, Error> {
+        let mut changes = vec![];
+
+        for entry in &mut self.entries {
+            let new = LockEntry::resolve(client, resolver, entry.name.clone(), &entry.spec())?;
+
+            if new != *entry {
+                changes.push(LockChange {
+                    old: std::mem::replace(entry, new.clone()),
+                    new,
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Checks every entry against the live catalog. An empty result means
+    /// the locked packages are still served unchanged.
+    pub fn verify(&self, client: &DiscoClient) -> Result<Vec<LockMismatch>, Error> {
+        let mut mismatches = vec![];
+
+        for entry in &self.entries {
+            let mismatch = |field, locked: &str, current: &str| LockMismatch {
+                name: entry.name.clone(),
+                platform: entry.platform.clone(),
+                field,
+                locked: locked.to_string(),
+                current: current.to_string(),
+            };
+
+            let info = match client.pull_package_info(entry.package_id.clone()) {
+                Ok(i) => i,
+                Err(Error::NotFound { .. }) => {
+                    mismatches.push(mismatch("package_id", &entry.package_id, ""));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let info = &info.result[0];
+            let (checksum_type, checksum) = client.package_checksum(info)?;
+
+            for (field, locked, current) in [
+                ("filename", &entry.filename, &info.filename),
+                ("direct_download_uri", &entry.direct_download_uri, &info.direct_download_uri),
+                ("checksum", &entry.checksum, &checksum),
+            ] {
+                if !locked.eq_ignore_ascii_case(current) {
+                    mismatches.push(mismatch(field, locked, current));
+                }
+            }
+
+            if entry.checksum_type != checksum_type {
+                mismatches.push(mismatch("checksum_type", entry.checksum_type.as_str(), checksum_type.as_str()));
+            }
+        }
+
+        Ok(mismatches)
+    }
+}
+
+impl LockEntry {
+    fn resolve<P: RankingPolicy>(
+        client: &DiscoClient,
+        resolver: &Resolver<P>,
+        name: String,
+        spec: &JdkSpec,
+    ) -> Result<Self, Error> {
+        let package = resolver.resolve_with(client, spec)?.package;
+        let info = client.pull_package_info(package.id.clone())?;
+        let info = &info.result[0];
+        let (checksum_type, checksum) = client.package_checksum(info)?;
+
+        Ok(Self {
+            name,
+            version: spec.version.clone(),
+            package_type: spec.package_type.clone(),
+            distributions: spec.distributions.clone(),
+            archive_types: spec.accepted_archive_types(),
+            javafx: spec.javafx,
+            platform: spec.platform.clone(),
+            package_id: package.id,
+            distribution: package.distribution,
+            java_version: package.java_version,
+            archive_type: package.archive_type,
+            filename: info.filename.clone(),
+            direct_download_uri: info.direct_download_uri.clone(),
+            checksum: checksum.to_lowercase(),
+            checksum_type,
+        })
+    }
+
+    /// The requirement this entry was resolved from.
+    pub fn spec(&self) -> JdkSpec {
+        JdkSpec::for_platform(self.version.clone(), self.platform.clone())
+            .package_type(self.package_type.clone())
+            .distributions(self.distributions.clone())
+            .archive_types(self.platform.operating_system.clone(), self.archive_types.clone())
+            .javafx(self.javafx)
+    }
+
+    /// Downloads the locked package into `dir` and verifies it against the
+    /// locked checksum, without asking the API. A file that doesn't match is
+    /// removed.
+    pub fn download(
+        &self,
+        client: &DiscoClient,
+        dir: impl AsRef<Path>,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<PathBuf, Error> {
+        let info = PackageInfoInner {
+            filename: self.filename.clone(),
+            direct_download_uri: self.direct_download_uri.clone(),
+            checksum: self.checksum.clone(),
+            checksum_type: self.checksum_type.clone(),
+            ..Default::default()
+        };
+
+        let path = client.download_package_info(&info, dir, None, progress)?;
+
+        if let Err(e) = checksum::verify_checksum_file(&path, &self.checksum_type, &self.checksum) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e);
+        }
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockfile_round_trip_test() {
+        let entry = LockEntry {
+            name: "default".to_string(),
+            version: VersionReq::parse("^21").unwrap(),
+            package_type: PackageType::Jdk,
+            distributions: vec!["temurin".to_string()],
+            archive_types: vec![ArchiveType::TarGz],
+            javafx: false,
+            platform: Platform::from_target_triple("aarch64-apple-darwin").unwrap(),
+            package_id: "abc".to_string(),
+            distribution: "temurin".to_string(),
+            java_version: JavaVersion::parse("21.0.2+13").unwrap(),
+            archive_type: ArchiveType::TarGz,
+            filename: "jdk.tar.gz".to_string(),
+            direct_download_uri: "https://example.com/jdk.tar.gz".to_string(),
+            checksum: "00".repeat(32),
+            checksum_type: ChecksumType::Sha256,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("jdk.lock.json");
+
+        let mut lockfile = Lockfile::new();
+        lockfile.entries.push(entry.clone());
+        lockfile.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded.entry("default", &entry.platform), Some(&entry));
+        assert_eq!(loaded.entries[0].spec().accepted_archive_types(), vec![ArchiveType::TarGz]);
+
+        std::fs::write(&path, r#"{"format_version": 99, "entries": []}"#).unwrap();
+        assert!(matches!(Lockfile::load(&path), Err(Error::UnsupportedLockfile(99))));
+    }
+}
@@ -1,23 +1,111 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::{
+    schema::{self, DecodeWarning, RawList, Schema, SchemaReport},
+    types::{ReleaseStatus, TermOfSupport},
+    version::JavaVersion,
+};
+
+/// A list of major versions. Entries that fail to decode are skipped and
+/// reported in `warnings`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(from = "RawList")]
 pub struct MajorVersionList {
     pub result: Vec<MajorVersion>,
     pub message: String,
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "RawList")]
+pub struct MajorVersionInfo {
+    pub result: [MajorVersion; 1],
+    pub message: String,
+    /// Versions that failed to decode and were left out.
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
+}
+
+/// A major version. Only `major_version` is required.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MajorVersion {
     pub major_version: u32,
-    pub term_of_support: String,
+    #[serde(default)]
+    pub term_of_support: TermOfSupport,
+    #[serde(default)]
     pub maintained: bool,
+    #[serde(default)]
     pub early_access_only: bool,
-    pub release_status: String,
-    pub versions: Vec<String>,
+    #[serde(default)]
+    pub release_status: ReleaseStatus,
+    /// Versions that fail to parse are left out and reported in the
+    /// containing response's `warnings`.
+    #[serde(default)]
+    pub versions: Vec<JavaVersion>,
+}
+
+impl From<RawList> for MajorVersionList {
+    fn from(mut raw: RawList) -> Self {
+        let mut warnings = schema::strip_invalid_elements::<JavaVersion>(&mut raw.result, "versions");
+        let (result, item_warnings) = schema::decode_items(raw.result);
+        warnings.extend(item_warnings);
+
+        Self {
+            result,
+            message: raw.message,
+            warnings,
+        }
+    }
+}
+
+impl TryFrom<RawList> for MajorVersionInfo {
+    type Error = String;
+
+    fn try_from(mut raw: RawList) -> Result<Self, Self::Error> {
+        let warnings = schema::strip_invalid_elements::<JavaVersion>(&mut raw.result, "versions");
+        let (major_version, message) = schema::decode_single::<MajorVersion>(raw)?;
+
+        Ok(Self {
+            result: [major_version],
+            message,
+            warnings,
+        })
+    }
+}
+
+impl Schema for MajorVersion {
+    const FIELDS: &'static [&'static str] = &[
+        "major_version",
+        "term_of_support",
+        "maintained",
+        "early_access_only",
+        "release_status",
+        "versions",
+    ];
 }
 
 impl MajorVersionList {
     pub fn decode(json: String) -> Result<Self, serde_json::Error> {
         serde_json::from_str(&json)
     }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        let mut report = schema::check_list::<MajorVersion>(json)?;
+        report.warnings = Self::decode(json.to_string())?.warnings;
+
+        Ok(report)
+    }
+}
+
+impl MajorVersionInfo {
+    pub fn decode(json: String) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(&json)
+    }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        schema::check_list::<MajorVersion>(json)
+    }
 }
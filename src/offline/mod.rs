@@ -0,0 +1,467 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    distributions::{DistributionInfo, DistributionList},
+    http::{response, DiscoClient, MajorVersionsQueryOptions, PackageQueryOptions},
+    major_versions::{MajorVersion, MajorVersionInfo, MajorVersionList},
+    package::{Package, PackageInfo, PackageList},
+    types::{Latest, MajorVersionQuery, PackageType, ReleaseStatus, TermOfSupport},
+    version::{JavaVersion, VersionReq},
+    Error,
+};
+
+/// A complete copy of the catalog, for answering queries without network
+/// access.
+///
+/// Export it on a connected machine with [`CatalogSnapshot::export`], copy the
+/// saved file over, then use the `pull_*` methods like on [`DiscoClient`].
+/// Filters are evaluated locally with the server's semantics, except
+/// `discovery_scope_id` and `include_versions`, which are ignored.
+/// `signature_available` only matches packages whose info was exported.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CatalogSnapshot {
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+    pub packages: PackageList,
+    /// Download details for `ids/{id}` lookups, by package id.
+    pub package_info: HashMap<String, PackageInfo>,
+    pub distributions: DistributionList,
+    pub major_versions: MajorVersionList,
+}
+
+impl CatalogSnapshot {
+    /// Pulls the whole catalog through `client`.
+    ///
+    /// Package info takes one request per package, so it's only pulled for
+    /// packages `with_package_info` returns `true` for.
+    pub fn export(
+        client: &DiscoClient,
+        mut with_package_info: impl FnMut(&Package) -> bool,
+    ) -> Result<Self, Error> {
+        let packages = client.pull_packages(None)?;
+        let distributions = client.pull_distributions()?;
+        let major_versions = client.pull_major_versions(Some(MajorVersionsQueryOptions {
+            early_access: Some(true),
+            general_availability: Some(true),
+            maintained: None,
+        }))?;
+
+        let mut package_info = HashMap::new();
+
+        for p in packages.result.iter().filter(|p| with_package_info(p)) {
+            package_info.insert(p.id.clone(), client.pull_package_info(p.id.clone())?);
+        }
+
+        Ok(Self {
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            packages,
+            package_info,
+            distributions,
+            major_versions,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(Error::Io)?;
+
+        response::decode(&path.as_ref().display().to_string(), &json)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let json = serde_json::to_vec(self)
+            .map_err(|e| response::json_error(&path.as_ref().display().to_string(), "", e))?;
+
+        std::fs::write(path, json).map_err(Error::Io)
+    }
+
+    pub fn pull_packages(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        let opts = query_opts.unwrap_or_default();
+
+        let version = opts.version
+            .as_deref()
+            .map(VersionReq::from_server_version)
+            .transpose()?;
+
+        let matching = self.packages.result
+            .iter()
+            .filter(|p| version.as_ref().is_none_or(|v| v.matches_any_release(&p.java_version)))
+            .filter(|p| package_matches(&opts, p))
+            .filter(|p| opts.signature_available.is_none_or(|s| self.has_signature(p) == s))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        Ok(PackageList {
+            result: match &opts.latest {
+                Some(latest) => apply_latest(latest, matching),
+                None => matching,
+            },
+            warnings: vec![],
+        })
+    }
+
+    pub fn pull_jdks(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        self.pull_packages(Some(PackageQueryOptions {
+            package_type: vec![PackageType::Jdk],
+            ..query_opts.unwrap_or_default()
+        }))
+    }
+
+    pub fn pull_jres(
+        &self,
+        query_opts: Option<PackageQueryOptions>,
+    ) -> Result<PackageList, Error> {
+        self.pull_packages(Some(PackageQueryOptions {
+            package_type: vec![PackageType::Jre],
+            ..query_opts.unwrap_or_default()
+        }))
+    }
+
+    fn has_signature(&self, package: &Package) -> bool {
+        self.package_info
+            .get(&package.id)
+            .is_some_and(|i| !i.result[0].signature_uri.is_empty())
+    }
+
+    pub fn pull_package_info(
+        &self,
+        package: String,
+    ) -> Result<PackageInfo, Error> {
+        self.package_info
+            .get(&package)
+            .cloned()
+            .ok_or(Error::NotFound { resource: "package", id: package, message: None })
+    }
+
+    pub fn pull_major_versions(
+        &self,
+        query_opts: Option<MajorVersionsQueryOptions>,
+    ) -> Result<MajorVersionList, Error> {
+        let opts = query_opts.unwrap_or_default();
+
+        Ok(MajorVersionList {
+            result: self.major_versions.result
+                .iter()
+                .filter(|v| major_version_matches(&opts, v))
+                .cloned()
+                .collect(),
+            message: self.major_versions.message.clone(),
+            warnings: vec![],
+        })
+    }
+
+    /// A single major version. With a release status, only its GA or EA
+    /// versions are listed.
+    pub fn pull_major_version(
+        &self,
+        major_version: u32,
+        release_status: Option<ReleaseStatus>,
+    ) -> Result<MajorVersionInfo, Error> {
+        let mut found = self.major_versions.result
+            .iter()
+            .find(|v| v.major_version == major_version)
+            .cloned()
+            .ok_or(Error::NotFound { resource: "major version", id: major_version.to_string(), message: None })?;
+
+        match release_status {
+            Some(ReleaseStatus::Ga) => found.versions.retain(|v| !v.is_pre_release()),
+            Some(ReleaseStatus::Ea) => found.versions.retain(|v| v.is_pre_release()),
+            _ => {}
+        }
+
+        Ok(MajorVersionInfo {
+            result: [found],
+            message: self.major_versions.message.clone(),
+            warnings: vec![],
+        })
+    }
+
+    /// The major versions selected by a named query, newest first.
+    ///
+    /// `useful` is answered with the maintained major versions.
+    pub fn pull_named_major_versions(
+        &self,
+        query: MajorVersionQuery,
+    ) -> Result<MajorVersionList, Error> {
+        let mut versions = self.major_versions.result.clone();
+        versions.sort_by_key(|v| std::cmp::Reverse(v.major_version));
+
+        let is_ea = |v: &MajorVersion| v.early_access_only || v.release_status == ReleaseStatus::Ea;
+
+        let result = match query {
+            MajorVersionQuery::LatestGa => versions.into_iter().filter(|v| !is_ea(v)).take(1).collect(),
+            MajorVersionQuery::LatestEa => versions.into_iter().filter(is_ea).take(1).collect(),
+            MajorVersionQuery::LatestLts => versions
+                .into_iter()
+                .filter(|v| !is_ea(v) && v.term_of_support == TermOfSupport::Lts)
+                .take(1)
+                .collect(),
+            MajorVersionQuery::LatestSts => versions
+                .into_iter()
+                .filter(|v| !is_ea(v) && v.term_of_support == TermOfSupport::Sts)
+                .take(1)
+                .collect(),
+            MajorVersionQuery::Useful => versions.into_iter().filter(|v| v.maintained).collect(),
+            MajorVersionQuery::Other(q) => return Err(Error::InvalidQuery(format!("unknown major version query `{}`", q))),
+        };
+
+        Ok(MajorVersionList {
+            result,
+            message: self.major_versions.message.clone(),
+            warnings: vec![],
+        })
+    }
+
+    /// The `direct_download_uri` of an exported package info.
+    pub fn package_download_uri(&self, package: String) -> Result<String, Error> {
+        Ok(self.pull_package_info(package)?.result[0].direct_download_uri.clone())
+    }
+
+    pub fn pull_distributions(&self) -> Result<DistributionList, Error> {
+        Ok(self.distributions.clone())
+    }
+
+    pub fn pull_distributions_for_version(
+        &self,
+        version: String,
+    ) -> Result<DistributionList, Error> {
+        let req = VersionReq::from_server_version(&version)?;
+
+        Ok(DistributionList {
+            result: self.distributions.result
+                .iter()
+                .filter(|d| d.versions.iter().any(|v| req.matches_any_release(v)))
+                .cloned()
+                .collect(),
+            message: self.distributions.message.clone(),
+            warnings: vec![],
+        })
+    }
+
+    pub fn pull_distribution_info(
+        &self,
+        distribution: String,
+    ) -> Result<DistributionInfo, Error> {
+        self.distributions.result
+            .iter()
+            .find(|d| d.api_parameter.eq_ignore_ascii_case(&distribution) || d.name.eq_ignore_ascii_case(&distribution))
+            .map(|d| DistributionInfo {
+                result: [d.clone()],
+                message: self.distributions.message.clone(),
+                warnings: vec![],
+            })
+            .ok_or(Error::NotFound { resource: "distribution", id: distribution, message: None })
+    }
+}
+
+fn package_matches(opts: &PackageQueryOptions, p: &Package) -> bool {
+    fn eq<T: PartialEq>(filter: &Option<T>, value: &T) -> bool {
+        filter.as_ref().is_none_or(|f| f == value)
+    }
+
+    fn any<T: PartialEq>(filter: &[T], value: &T) -> bool {
+        filter.is_empty() || filter.contains(value)
+    }
+
+    (opts.distribution.is_empty() || opts.distribution.iter().any(|d| d.eq_ignore_ascii_case(&p.distribution)))
+        // The server treats aliases like amd64 and x64 as the same
+        && (opts.architecture.is_empty() || opts.architecture.iter().any(|a| a.normalized() == p.architecture.normalized()))
+        && any(&opts.archive_type, &p.archive_type)
+        && any(&opts.package_type, &p.package_type)
+        && any(&opts.operating_system, &p.operating_system)
+        && any(&opts.libc_type, &p.lib_c_type)
+        && any(&opts.release_status, &p.release_status)
+        && any(&opts.term_of_support, &p.term_of_support)
+        && (opts.bitness.is_empty() || p.architecture.bitness().is_some_and(|b| opts.bitness.contains(&b)))
+        && eq(&opts.javafx_bundled, &p.javafx_bundled)
+        && eq(&opts.directly_downloadable, &p.directly_downloadable)
+        && eq(&opts.jdk_version, &p.jdk_version)
+        && any(&opts.fpu, &p.fpu)
+        && eq(&opts.free_to_use_in_production, &p.free_use_in_production)
+        && eq(&opts.tck_tested, &p.tck_tested)
+        && eq(&opts.aqavit_certified, &p.aqavit_certified)
+        && (opts.feature.is_empty() || p.feature.iter().any(|f| opts.feature.iter().any(|o| o.eq_ignore_ascii_case(&f.api_string))))
+}
+
+/// Reduces the matching packages like the server's `latest` parameter:
+///
+/// - `available`, `per_distro`: the packages of the newest version of each
+///   distribution
+/// - `per_version`: the packages of the newest version of each distribution
+///   and major version
+/// - `all_of_version`: the packages of the newest version across all
+///   distributions
+fn apply_latest(latest: &Latest, packages: Vec<Package>) -> Vec<Package> {
+    let key = |p: &Package| -> (String, Option<u32>) {
+        match latest {
+            Latest::PerVersion => (p.distribution.to_lowercase(), Some(p.major_version)),
+            Latest::AllOfVersion => (String::new(), None),
+            _ => (p.distribution.to_lowercase(), None),
+        }
+    };
+
+    let mut newest: HashMap<(String, Option<u32>), JavaVersion> = HashMap::new();
+
+    for p in &packages {
+        match newest.get(&key(p)) {
+            Some(v) if *v >= p.java_version => {}
+            _ => {
+                newest.insert(key(p), p.java_version.clone());
+            }
+        }
+    }
+
+    packages
+        .into_iter()
+        .filter(|p| newest.get(&key(p)) == Some(&p.java_version))
+        .collect()
+}
+
+fn major_version_matches(opts: &MajorVersionsQueryOptions, v: &MajorVersion) -> bool {
+    opts.maintained.is_none_or(|m| v.maintained == m)
+        && (opts.early_access != Some(false) || !v.early_access_only)
+        && (opts.general_availability != Some(false) || v.early_access_only)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Architecture, OperatingSystem, Verification};
+
+    fn package(id: &str, distribution: &str, java_version: &str, os: &str) -> serde_json::Value {
+        crate::test_support::package_json(id, distribution, java_version, serde_json::json!({ "operating_system": os }))
+    }
+
+    fn major_version(major: u32, support: &str, status: &str, maintained: bool, versions: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "major_version": major, "term_of_support": support, "maintained": maintained,
+            "early_access_only": status == "ea", "release_status": status, "versions": versions,
+        })
+    }
+
+    fn snapshot() -> CatalogSnapshot {
+        serde_json::from_value(serde_json::json!({
+            "created_at": 0,
+            "packages": { "result": [
+                package("a", "temurin", "17.0.8+7", "linux"),
+                package("b", "temurin", "17.0.9+9", "linux"),
+                package("c", "zulu", "17.0.9+8", "linux"),
+                package("d", "temurin", "21.0.1+12", "linux"),
+                package("e", "temurin", "17.0.9+9", "windows"),
+            ]},
+            "package_info": {
+                "a": { "result": [{ "filename": "a.tar.gz", "direct_download_uri": "https://example.com/a.tar.gz" }] },
+            },
+            "distributions": { "result": [], "message": "" },
+            "major_versions": { "result": [
+                major_version(23, "sts", "ea", true, &["23-ea+5"]),
+                major_version(22, "sts", "ga", true, &["22.0.1", "22"]),
+                major_version(21, "lts", "ga", true, &["21.0.3-ea+1", "21.0.2", "21.0.1"]),
+                major_version(11, "lts", "ga", false, &["11.0.22"]),
+            ]},
+        })).unwrap()
+    }
+
+    fn ids(list: PackageList) -> Vec<String> {
+        list.result.into_iter().map(|p| p.id).collect()
+    }
+
+    #[test]
+    fn snapshot_pull_packages_test() {
+        let s = snapshot();
+
+        let linux_17 = s.pull_packages(Some(PackageQueryOptions {
+            version: Some("17".to_string()),
+            operating_system: vec![OperatingSystem::Linux],
+            architecture: vec![Architecture::X64],
+            ..Default::default()
+        })).unwrap();
+        assert_eq!(ids(linux_17), vec!["a", "b", "c"]);
+
+        let alias = s.pull_packages(Some(PackageQueryOptions {
+            version: Some("17".to_string()),
+            operating_system: vec![OperatingSystem::Linux],
+            architecture: vec![Architecture::Amd64],
+            ..Default::default()
+        })).unwrap();
+        assert_eq!(ids(alias), vec!["a", "b", "c"]);
+
+        let range = s.pull_packages(Some(PackageQueryOptions {
+            version: Some("17.0.9..<22".to_string()),
+            distribution: vec!["temurin".to_string()],
+            ..Default::default()
+        })).unwrap();
+        assert_eq!(ids(range), vec!["b", "d", "e"]);
+
+        let either = s.pull_packages(Some(PackageQueryOptions {
+            operating_system: vec![OperatingSystem::Linux, OperatingSystem::Windows],
+            distribution: vec!["temurin".to_string()],
+            ..Default::default()
+        })).unwrap();
+        assert_eq!(ids(either).len(), 4);
+
+        let latest = s.pull_packages(Some(PackageQueryOptions {
+            version: Some("17".to_string()),
+            latest: Some(Latest::Available),
+            ..Default::default()
+        })).unwrap();
+        assert_eq!(ids(latest), vec!["b", "c", "e"]);
+
+        let certified = s.pull_packages(Some(PackageQueryOptions {
+            tck_tested: Some(Verification::Yes),
+            aqavit_certified: Some(Verification::Yes),
+            ..Default::default()
+        })).unwrap();
+        assert!(certified.result.is_empty());
+
+        assert!(matches!(s.pull_package_info("x".to_string()), Err(Error::NotFound { .. })));
+    }
+
+    #[test]
+    fn snapshot_pull_major_version_test() {
+        let s = snapshot();
+
+        let ga = s.pull_major_version(21, Some(ReleaseStatus::Ga)).unwrap();
+        assert_eq!(ga.result[0].versions.iter().map(|v| v.to_string()).collect::<Vec<_>>(), vec!["21.0.2", "21.0.1"]);
+
+        let ea = s.pull_major_version(21, Some(ReleaseStatus::Ea)).unwrap();
+        assert_eq!(ea.result[0].versions.len(), 1);
+
+        assert!(matches!(s.pull_major_version(8, None), Err(Error::NotFound { resource: "major version", .. })));
+    }
+
+    #[test]
+    fn snapshot_named_major_versions_test() {
+        let s = snapshot();
+
+        let named = |q| s.pull_named_major_versions(q).unwrap().result.iter().map(|v| v.major_version).collect::<Vec<_>>();
+
+        assert_eq!(named(MajorVersionQuery::LatestGa), vec![22]);
+        assert_eq!(named(MajorVersionQuery::LatestEa), vec![23]);
+        assert_eq!(named(MajorVersionQuery::LatestLts), vec![21]);
+        assert_eq!(named(MajorVersionQuery::LatestSts), vec![22]);
+        assert_eq!(named(MajorVersionQuery::Useful), vec![23, 22, 21]);
+        assert!(matches!(s.pull_named_major_versions(MajorVersionQuery::from("nope")), Err(Error::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn snapshot_package_download_uri_test() {
+        let s = snapshot();
+
+        assert_eq!(s.package_download_uri("a".to_string()).unwrap(), "https://example.com/a.tar.gz");
+        assert!(matches!(s.package_download_uri("b".to_string()), Err(Error::NotFound { resource: "package", .. })));
+    }
+}
@@ -1,48 +1,90 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::{
+    schema::{self, DecodeWarning, RawList, Schema, SchemaReport},
+    types::{Architecture, ArchiveType, ChecksumType, Fpu, LibCType, OperatingSystem, PackageType, ReleaseStatus, TermOfSupport, Verification},
+    version::{JavaVersion, VersionReq},
+};
+
+/// A list of packages.
+///
+/// Packages that fail to decode are skipped and reported in `warnings`
+/// instead of failing the whole list.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(from = "RawList")]
 pub struct PackageList {
     pub result: Vec<Package>,
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
 }
 
+/// A package. Only `id` and `java_version` are required, other missing or
+/// `null` fields fall back to their defaults.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Package {
     pub id: String,
-    pub archive_type: String,
+    #[serde(default)]
+    pub archive_type: ArchiveType,
+    #[serde(default)]
     pub distribution: String,
+    #[serde(default)]
     pub major_version: u32,
-    pub java_version: String,
-    pub distribution_version: String,
+    pub java_version: JavaVersion,
+    #[serde(default)]
+    pub distribution_version: Option<JavaVersion>,
+    #[serde(default)]
     pub jdk_version: u32,
+    #[serde(default)]
     pub latest_build_available: bool,
-    pub release_status: String,
-    pub term_of_support: String,
-    pub operating_system: String,
-    pub lib_c_type: String,
-    pub architecture: String,
-    pub fpu: String,
-    pub package_type: String,
+    #[serde(default)]
+    pub release_status: ReleaseStatus,
+    #[serde(default)]
+    pub term_of_support: TermOfSupport,
+    #[serde(default)]
+    pub operating_system: OperatingSystem,
+    #[serde(default)]
+    pub lib_c_type: LibCType,
+    #[serde(default)]
+    pub architecture: Architecture,
+    #[serde(default)]
+    pub fpu: Fpu,
+    #[serde(default)]
+    pub package_type: PackageType,
+    #[serde(default)]
     pub javafx_bundled: bool,
+    #[serde(default)]
     pub directly_downloadable: bool,
+    #[serde(default)]
     pub filename: String,
+    #[serde(default)]
     pub links: HashMap<String, String>,
+    #[serde(default)]
     pub free_use_in_production: bool,
-    pub tck_tested: String,
+    #[serde(default)]
+    pub tck_tested: Verification,
+    #[serde(default)]
     pub tck_cert_uri: String,
-    pub aqavit_certified: String,
+    #[serde(default)]
+    pub aqavit_certified: Verification,
+    #[serde(default)]
     pub aqavit_cert_uri: String,
+    #[serde(default)]
     pub size: i64,
+    #[serde(default)]
     pub feature: Vec<Feature>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "RawList")]
 pub struct PackageInfo {
     pub result: [PackageInfoInner; 1],
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct PackageInfoInner {
     pub filename: String,
     pub direct_download_uri: String,
@@ -50,24 +92,154 @@ pub struct PackageInfoInner {
     pub signature_uri: String,
     pub checksum_uri: String,
     pub checksum: String,
-    pub checksum_type: String,
+    pub checksum_type: ChecksumType,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Feature {
     pub name: String,
     pub ui_string: String,
     pub api_string: String,
 }
 
+impl From<RawList> for PackageList {
+    fn from(raw: RawList) -> Self {
+        let (result, warnings) = schema::decode_items(raw.result);
+
+        Self { result, warnings }
+    }
+}
+
+impl TryFrom<RawList> for PackageInfo {
+    type Error = String;
+
+    fn try_from(raw: RawList) -> Result<Self, Self::Error> {
+        let (info, message) = schema::decode_single::<PackageInfoInner>(raw)?;
+
+        Ok(Self {
+            result: [info],
+            message,
+        })
+    }
+}
+
+impl Schema for Package {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "archive_type",
+        "distribution",
+        "major_version",
+        "java_version",
+        "distribution_version",
+        "jdk_version",
+        "latest_build_available",
+        "release_status",
+        "term_of_support",
+        "operating_system",
+        "lib_c_type",
+        "architecture",
+        "fpu",
+        "package_type",
+        "javafx_bundled",
+        "directly_downloadable",
+        "filename",
+        "links",
+        "free_use_in_production",
+        "tck_tested",
+        "tck_cert_uri",
+        "aqavit_certified",
+        "aqavit_cert_uri",
+        "size",
+        "feature",
+    ];
+
+    fn check_nested(field: &str, value: &Value, path: &str, report: &mut SchemaReport) {
+        if let ("feature", Some(features)) = (field, value.as_array()) {
+            for (i, f) in features.iter().enumerate() {
+                schema::check_item::<Feature>(f, &format!("{}[{}]", path, i), report);
+            }
+        }
+    }
+}
+
+impl Schema for PackageInfoInner {
+    const FIELDS: &'static [&'static str] = &[
+        "filename",
+        "direct_download_uri",
+        "download_site_uri",
+        "signature_uri",
+        "checksum_uri",
+        "checksum",
+        "checksum_type",
+    ];
+}
+
+impl Schema for Feature {
+    const FIELDS: &'static [&'static str] = &["name", "ui_string", "api_string"];
+}
+
 impl PackageList {
     pub fn decode(json: String) -> Result<Self, serde_json::Error> {
         serde_json::from_str(&json)
     }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        let mut report = schema::check_list::<Package>(json)?;
+        report.warnings = Self::decode(json.to_string())?.warnings;
+
+        Ok(report)
+    }
+
+    /// Keeps only the packages whose `java_version` matches `req`.
+    pub fn filter_version(mut self, req: &VersionReq) -> Self {
+        self.result.retain(|p| req.matches(&p.java_version));
+        self
+    }
 }
 
 impl PackageInfo {
     pub fn decode(json: String) -> Result<Self, serde_json::Error> {
         serde_json::from_str(&json)
     }
+
+    /// See [`SchemaReport`].
+    pub fn check_schema(json: &str) -> Result<SchemaReport, serde_json::Error> {
+        schema::check_list::<PackageInfoInner>(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_lenient_package_list_test() {
+        let list = PackageList::decode(r#"{"result": [
+            {"id": "a", "java_version": "17.0.9+9", "aqavit_cert_uri": null, "size": 42},
+            {"id": "b", "java_version": "not a version"},
+            {"java_version": "21"}
+        ]}"#.to_string()).unwrap();
+
+        assert_eq!(list.result.len(), 1);
+        assert_eq!(list.result[0].aqavit_cert_uri, "");
+        assert_eq!(list.result[0].size, 42);
+
+        assert_eq!(list.warnings.len(), 2);
+        assert_eq!((list.warnings[0].index, list.warnings[0].id.as_deref()), (1, Some("b")));
+        assert_eq!((list.warnings[1].index, list.warnings[1].id.as_deref()), (2, None));
+    }
+
+    #[test]
+    fn check_package_schema_test() {
+        let report = PackageList::check_schema(r#"{"result": [
+            {"id": "a", "java_version": "17", "new_field": 1, "feature": [{"name": "crac", "ui_string": "CRaC"}]}
+        ]}"#).unwrap();
+
+        assert_eq!(report.unknown_fields, vec!["result[0].new_field"]);
+        assert!(report.missing_fields.contains(&"result[0].archive_type".to_string()));
+        assert!(report.missing_fields.contains(&"result[0].feature[0].api_string".to_string()));
+        assert!(!report.is_clean());
+    }
 }
@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use crate::{
+    http::PackageQueryOptions,
+    types::{Architecture, Bitness, LibCType, OperatingSystem},
+    Error,
+};
+
+/// The platform values Disco uses to select packages.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Platform {
+    pub operating_system: OperatingSystem,
+    pub architecture: Architecture,
+    pub libc_type: LibCType,
+    pub bitness: Bitness,
+}
+
+impl Platform {
+    /// Detects the platform this process runs on.
+    ///
+    /// On Linux the C library is detected at runtime, so a glibc build of this
+    /// crate running on Alpine still resolves musl packages.
+    pub fn current() -> Self {
+        let architecture = match std::env::consts::ARCH {
+            "powerpc64" if cfg!(target_endian = "little") => Architecture::Ppc64le,
+            arch => architecture_from_rust(arch).unwrap_or_else(|| Architecture::Other(arch.to_string())),
+        };
+
+        let operating_system = operating_system_from_rust(std::env::consts::OS)
+            .unwrap_or_else(|| OperatingSystem::Other(std::env::consts::OS.to_string()));
+
+        let libc_type = match operating_system {
+            OperatingSystem::Linux => detect_linux_libc(),
+            ref os => default_libc(os),
+        };
+
+        let bitness = if cfg!(target_pointer_width = "64") { Bitness::Bit64 } else { Bitness::Bit32 };
+
+        Self {
+            operating_system,
+            architecture,
+            libc_type,
+            bitness,
+        }
+    }
+
+    /// The platform for a Rust target triple, like `aarch64-unknown-linux-musl`.
+    pub fn from_target_triple(triple: &str) -> Result<Self, Error> {
+        let unsupported = || Error::UnsupportedPlatform(triple.to_string());
+
+        let mut parts = triple.split('-');
+        let arch = parts.next().ok_or_else(unsupported)?;
+        let rest = parts.collect::<Vec<_>>();
+
+        let architecture = architecture_from_rust(arch).ok_or_else(unsupported)?;
+
+        let operating_system = rest
+            .iter()
+            .find_map(|p| match *p {
+                "darwin" => Some(OperatingSystem::Macos),
+                "illumos" => Some(OperatingSystem::Solaris),
+                p => operating_system_from_rust(p),
+            })
+            .ok_or_else(unsupported)?;
+
+        let libc_type = match operating_system {
+            OperatingSystem::Linux if rest.iter().any(|p| p.starts_with("musl")) => LibCType::Musl,
+            ref os => default_libc(os),
+        };
+
+        let bitness = architecture.bitness().unwrap_or(Bitness::Bit32);
+
+        Ok(Self {
+            operating_system,
+            architecture,
+            libc_type,
+            bitness,
+        })
+    }
+
+    /// Query options selecting packages for this platform.
+    pub fn query_options(&self) -> PackageQueryOptions {
+        let mut opts = PackageQueryOptions::default();
+        self.merge_into(&mut opts);
+        opts
+    }
+
+    /// Fills the platform fields of `opts` that are still empty.
+    pub fn merge_into(&self, opts: &mut PackageQueryOptions) {
+        fn fill<T: Clone>(filter: &mut Vec<T>, value: &T) {
+            if filter.is_empty() {
+                filter.push(value.clone());
+            }
+        }
+
+        fill(&mut opts.operating_system, &self.operating_system);
+        fill(&mut opts.architecture, &self.architecture);
+        fill(&mut opts.libc_type, &self.libc_type);
+        fill(&mut opts.bitness, &self.bitness);
+    }
+}
+
+fn architecture_from_rust(arch: &str) -> Option<Architecture> {
+    Some(match arch {
+        "x86_64" => Architecture::X64,
+        "x86" | "i386" | "i586" | "i686" => Architecture::X86,
+        "aarch64" | "arm64" => Architecture::Aarch64,
+        "powerpc64le" => Architecture::Ppc64le,
+        "powerpc64" => Architecture::Ppc64,
+        "powerpc" => Architecture::Ppc,
+        "s390x" => Architecture::S390x,
+        "sparcv9" | "sparc64" => Architecture::Sparcv9,
+        a if a.starts_with("arm") || a.starts_with("thumbv7") => Architecture::Arm,
+        a if a.starts_with("riscv64") => Architecture::Riscv64,
+        a if a.starts_with("mips") => Architecture::Mips,
+        _ => return None,
+    })
+}
+
+fn operating_system_from_rust(os: &str) -> Option<OperatingSystem> {
+    Some(match os {
+        "linux" => OperatingSystem::Linux,
+        "macos" => OperatingSystem::Macos,
+        "windows" => OperatingSystem::Windows,
+        "solaris" | "illumos" => OperatingSystem::Solaris,
+        "aix" => OperatingSystem::Aix,
+        _ => return None,
+    })
+}
+
+fn default_libc(os: &OperatingSystem) -> LibCType {
+    match os {
+        OperatingSystem::Windows => LibCType::CStdLib,
+        OperatingSystem::Linux => LibCType::Glibc,
+        _ => LibCType::Libc,
+    }
+}
+
+/// Checks the dynamic loader this process runs under, then asks `ldd`, then
+/// looks for an installed musl loader. Defaults to glibc.
+///
+/// A musl loader alone isn't conclusive, Debian's `musl` package installs
+/// one next to glibc.
+fn detect_linux_libc() -> LibCType {
+    // Statically linked builds have no loader mapped
+    if let Some(libc) = std::fs::read_to_string("/proc/self/maps").ok().and_then(|m| libc_from_maps(&m)) {
+        return libc;
+    }
+
+    // musl's ldd prints its banner to stderr, glibc's to stdout
+    if let Ok(output) = std::process::Command::new("ldd").arg("--version").output() {
+        let banner = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr))
+            .to_lowercase();
+
+        if banner.contains("musl") {
+            return LibCType::Musl;
+        }
+
+        if banner.contains("glibc") || banner.contains("gnu libc") {
+            return LibCType::Glibc;
+        }
+    }
+
+    let has_musl_loader = ["/lib", "/usr/lib"].iter().any(|dir| {
+        std::fs::read_dir(dir).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|e| e.file_name().to_string_lossy().starts_with("ld-musl-"))
+        })
+    });
+
+    if has_musl_loader {
+        LibCType::Musl
+    } else {
+        LibCType::Glibc
+    }
+}
+
+/// The libc of the dynamic loader mapped in a `/proc/<pid>/maps` listing.
+fn libc_from_maps(maps: &str) -> Option<LibCType> {
+    maps.lines()
+        .filter_map(|l| l.split_whitespace().nth(5))
+        .filter_map(|path| path.rsplit('/').next())
+        .find_map(|name| {
+            if name.starts_with("ld-musl-") {
+                Some(LibCType::Musl)
+            } else if name.starts_with("ld-linux") {
+                Some(LibCType::Glibc)
+            } else {
+                None
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform_from_target_triple_test() {
+        let p = Platform::from_target_triple("aarch64-unknown-linux-musl").unwrap();
+        assert_eq!(
+            (p.operating_system, p.architecture, p.libc_type, p.bitness),
+            (OperatingSystem::Linux, Architecture::Aarch64, LibCType::Musl, Bitness::Bit64),
+        );
+
+        let p = Platform::from_target_triple("x86_64-apple-darwin").unwrap();
+        assert_eq!((p.operating_system, p.architecture), (OperatingSystem::Macos, Architecture::X64));
+
+        let p = Platform::from_target_triple("i686-pc-windows-msvc").unwrap();
+        assert_eq!(
+            (p.operating_system, p.libc_type, p.bitness),
+            (OperatingSystem::Windows, LibCType::CStdLib, Bitness::Bit32),
+        );
+
+        assert!(Platform::from_target_triple("wasm32-unknown-unknown").is_err());
+    }
+
+    #[test]
+    fn platform_merge_test() {
+        let mut opts = PackageQueryOptions {
+            architecture: vec![Architecture::X86],
+            ..Default::default()
+        };
+
+        Platform::from_target_triple("x86_64-unknown-linux-gnu").unwrap().merge_into(&mut opts);
+
+        assert_eq!(opts.architecture, vec![Architecture::X86]);
+        assert_eq!(opts.operating_system, vec![OperatingSystem::Linux]);
+        assert_eq!(opts.libc_type, vec![LibCType::Glibc]);
+    }
+
+    #[test]
+    fn libc_from_maps_test() {
+        let maps = |loader: &str| format!(
+            "55d0c0a00000-55d0c0a28000 r--p 00000000 08:01 1234 /usr/bin/foojay\n\
+             7f3b1c000000-7f3b1c022000 r--p 00000000 08:01 5678 {}\n\
+             7ffc8a7e0000-7ffc8a801000 rw-p 00000000 00:00 0 [stack]\n",
+            loader,
+        );
+
+        assert_eq!(libc_from_maps(&maps("/lib/ld-musl-x86_64.so.1")), Some(LibCType::Musl));
+        assert_eq!(libc_from_maps(&maps("/usr/lib/x86_64-linux-gnu/ld-linux-x86-64.so.2")), Some(LibCType::Glibc));
+        assert_eq!(libc_from_maps(&maps("/usr/lib/libfoo.so")), None);
+    }
+}
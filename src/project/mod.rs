@@ -0,0 +1,329 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    http::PackageQueryOptions,
+    resolve::JdkSpec,
+    version::VersionReq,
+    Error,
+};
+
+/// The files a project can declare its Java version in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionFileKind {
+    /// sdkman's `.sdkmanrc`, like `java=21.0.2-tem`.
+    Sdkmanrc,
+    /// asdf's `.tool-versions`, like `java temurin-21.0.2+13.0.LTS`.
+    ToolVersions,
+    /// `.java-version`, like `21` or `temurin-21.0.2`.
+    JavaVersion,
+    /// Maven's `maven.compiler.release` or `java.version` property.
+    Pom,
+}
+
+impl VersionFileKind {
+    /// All kinds, in the order they are looked for within a directory.
+    pub const ALL: &'static [VersionFileKind] = &[
+        VersionFileKind::Sdkmanrc,
+        VersionFileKind::ToolVersions,
+        VersionFileKind::JavaVersion,
+        VersionFileKind::Pom,
+    ];
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            VersionFileKind::Sdkmanrc => ".sdkmanrc",
+            VersionFileKind::ToolVersions => ".tool-versions",
+            VersionFileKind::JavaVersion => ".java-version",
+            VersionFileKind::Pom => "pom.xml",
+        }
+    }
+}
+
+/// A Java version declared by a project file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectVersion {
+    pub path: PathBuf,
+    pub kind: VersionFileKind,
+    /// The declared value, as written.
+    pub raw: String,
+    pub version: VersionReq,
+    /// The Disco `api_parameter` of the declared distribution, if any.
+    pub distribution: Option<String>,
+    /// Whether a JavaFX build was asked for, like sdkman's `21.0.2.fx-zulu`.
+    pub javafx: bool,
+}
+
+impl ProjectVersion {
+    /// Walks up from `dir` and returns the first declared version.
+    ///
+    /// Within a directory the files are tried in [`VersionFileKind::ALL`]
+    /// order. Files without a Java entry are skipped, malformed ones fail.
+    pub fn find(dir: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        for dir in dir.as_ref().ancestors() {
+            for kind in VersionFileKind::ALL {
+                let path = dir.join(kind.file_name());
+
+                if !path.is_file() {
+                    continue;
+                }
+
+                let content = std::fs::read_to_string(&path).map_err(Error::Io)?;
+
+                if let Some(v) = Self::parse(*kind, &path, &content)? {
+                    return Ok(Some(v));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Parses the content of a version file, `None` if it doesn't declare a
+    /// Java version.
+    pub fn parse(kind: VersionFileKind, path: impl Into<PathBuf>, content: &str) -> Result<Option<Self>, Error> {
+        let path = path.into();
+
+        let raw = match kind {
+            VersionFileKind::Sdkmanrc => lines(content)
+                .find_map(|l| l.strip_prefix("java").map(str::trim_start)?.strip_prefix('='))
+                .map(str::trim),
+            VersionFileKind::ToolVersions => lines(content)
+                .find_map(|l| l.strip_prefix("java ").or_else(|| l.strip_prefix("java\t")))
+                .and_then(|v| v.split_whitespace().next()),
+            VersionFileKind::JavaVersion => lines(content).next(),
+            VersionFileKind::Pom => ["maven.compiler.release", "java.version"]
+                .iter()
+                .find_map(|t| xml_element(content, t).filter(|v| !v.contains("${"))),
+        };
+
+        let Some(raw) = raw.filter(|r| !r.is_empty()) else {
+            return Ok(None);
+        };
+
+        let invalid = |message: &str| Error::InvalidVersionFile {
+            path: path.clone(),
+            message: format!("{}: `{}`", message, raw),
+        };
+
+        let (version, distribution, javafx) = match kind {
+            VersionFileKind::Sdkmanrc => {
+                let (version, suffix) = raw.rsplit_once('-').ok_or_else(|| invalid("missing vendor suffix"))?;
+                let distribution = sdkman_distribution(suffix).ok_or_else(|| invalid("unknown sdkman vendor"))?;
+
+                match version.strip_suffix(".fx") {
+                    Some(v) => (v.to_string(), Some(distribution.to_string()), true),
+                    None => (version.to_string(), Some(distribution.to_string()), false),
+                }
+            }
+            VersionFileKind::ToolVersions | VersionFileKind::JavaVersion => {
+                let (name, version) = split_distribution(raw);
+                let distribution = name.map(distribution_from_name);
+                let version = match distribution.as_deref() {
+                    Some(d) => distribution_to_java_version(d, version),
+                    None => version.to_string(),
+                };
+
+                (version, distribution, false)
+            }
+            VersionFileKind::Pom => {
+                // Old style `1.8` means 8
+                let version = match raw.strip_prefix("1.") {
+                    Some(v) if v.parse::<u32>().is_ok_and(|v| v <= 8) => v,
+                    _ => raw,
+                };
+
+                (version.to_string(), None, false)
+            }
+        };
+
+        let version = VersionReq::parse(&version).map_err(|_| invalid("invalid version"))?;
+
+        Ok(Some(Self {
+            path,
+            kind,
+            raw: raw.to_string(),
+            version,
+            distribution,
+            javafx,
+        }))
+    }
+
+    /// A resolver spec for the current platform. The declared distribution
+    /// becomes the preferred one.
+    pub fn spec(&self) -> JdkSpec {
+        JdkSpec::new(self.version.clone())
+            .distributions(self.distribution.clone())
+            .javafx(self.javafx)
+    }
+
+    /// Query options for the current platform, limited to the declared
+    /// distribution.
+    pub fn query_options(&self) -> PackageQueryOptions {
+        PackageQueryOptions {
+            distribution: self.distribution.iter().cloned().collect(),
+            ..self.spec().query_options()
+        }
+    }
+}
+
+/// Maps an sdkman vendor suffix, like `tem` in `21.0.2-tem`, to the Disco
+/// distribution `api_parameter`.
+pub fn sdkman_distribution(suffix: &str) -> Option<&'static str> {
+    Some(match suffix {
+        "albba" => "dragonwell",
+        "amzn" => "corretto",
+        "bisheng" => "bisheng",
+        "graal" => "graalvm",
+        "graalce" => "graalvm_community",
+        "jbr" => "jetbrains",
+        "kona" => "kona",
+        "librca" => "liberica",
+        "mandrel" => "mandrel",
+        "ms" => "microsoft",
+        "nik" => "liberica_native",
+        "open" => "oracle_open_jdk",
+        "oracle" => "oracle",
+        "sapmchn" => "sap_machine",
+        "sem" => "semeru",
+        "tem" => "temurin",
+        "trava" => "trava",
+        "zulu" => "zulu",
+        _ => return None,
+    })
+}
+
+/// Maps the distribution names asdf and `.java-version` use to the Disco
+/// `api_parameter`. Unknown names are passed through.
+fn distribution_from_name(name: &str) -> String {
+    let name = name.to_lowercase();
+
+    let mapped = match name.as_str() {
+        "adoptopenjdk" | "adoptopenjdk-openj9" => "aoj",
+        "amazon" | "amazon-corretto" => "corretto",
+        "graalvm-community" => "graalvm_community",
+        "openjdk" => "oracle_open_jdk",
+        "sapmachine" => "sap_machine",
+        "semeru-openj9" | "ibm-semeru" => "semeru",
+        n => return sdkman_distribution(n).map(String::from).unwrap_or_else(|| n.replace('-', "_")),
+    };
+
+    mapped.to_string()
+}
+
+/// Splits `temurin-21.0.2` into its distribution and version. The
+/// distribution ends at the first `-` followed by a digit.
+fn split_distribution(raw: &str) -> (Option<&str>, &str) {
+    raw.match_indices('-')
+        .find(|(i, _)| raw[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        .map(|(i, _)| (Some(&raw[..i]), &raw[i + 1..]))
+        .unwrap_or((None, raw))
+}
+
+/// asdf lists some distributions by their own version scheme, like
+/// `zulu-21.32.17` or `corretto-17.0.9.8.1`. Keeps what identifies the Java
+/// version.
+fn distribution_to_java_version(distribution: &str, version: &str) -> String {
+    let version = version
+        .trim_end_matches(".LTS")
+        .trim_end_matches("-LTS");
+
+    let keep = match distribution {
+        "zulu" => 1,
+        _ => 3,
+    };
+
+    let (numbers, build) = match version.split_once('+') {
+        Some((n, b)) => (n, Some(b)),
+        None => (version, None),
+    };
+
+    let components = numbers.split('.').collect::<Vec<_>>();
+
+    match build {
+        Some(b) if components.len() <= keep => format!("{}+{}", numbers, b.split('.').next().unwrap_or(b)),
+        _ => components[..components.len().min(keep)].join("."),
+    }
+}
+
+/// Non-empty lines without `#` comments.
+fn lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim())
+        .filter(|l| !l.is_empty())
+}
+
+fn xml_element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+
+    Some(xml[start..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(kind: VersionFileKind, content: &str) -> ProjectVersion {
+        ProjectVersion::parse(kind, kind.file_name(), content).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parse_version_files_test() {
+        let sdkman = parse(VersionFileKind::Sdkmanrc, "# Enable auto-env\njava=21.0.2.fx-zulu\nmaven=3.9.6\n");
+        assert_eq!(sdkman.version.to_string(), "21.0.2");
+        assert_eq!(sdkman.distribution.as_deref(), Some("zulu"));
+        assert!(sdkman.javafx);
+
+        let asdf = parse(VersionFileKind::ToolVersions, "nodejs 20.11.0\njava temurin-21.0.2+13.0.LTS\n");
+        assert_eq!(asdf.version.to_string(), "21.0.2+13");
+        assert_eq!(asdf.distribution.as_deref(), Some("temurin"));
+
+        let asdf = parse(VersionFileKind::ToolVersions, "java graalvm-community-21.0.2");
+        assert_eq!(asdf.distribution.as_deref(), Some("graalvm_community"));
+
+        let asdf = parse(VersionFileKind::ToolVersions, "java zulu-21.32.17");
+        assert_eq!(asdf.version.to_string(), "21");
+
+        let java_version = parse(VersionFileKind::JavaVersion, "17\n");
+        assert_eq!(java_version.version.to_string(), "17");
+        assert_eq!(java_version.distribution, None);
+
+        let pom = parse(VersionFileKind::Pom, "<properties>\n  <java.version>1.8</java.version>\n</properties>");
+        assert_eq!(pom.version.to_string(), "8");
+
+        // Spring Boot style, the release property refers to java.version
+        let pom = parse(VersionFileKind::Pom, concat!(
+            "<properties>\n",
+            "  <java.version>17</java.version>\n",
+            "  <maven.compiler.release>${java.version}</maven.compiler.release>\n",
+            "</properties>",
+        ));
+        assert_eq!(pom.version.to_string(), "17");
+
+        assert!(ProjectVersion::parse(VersionFileKind::Pom, "pom.xml", "<java.version>${jdk}</java.version>").unwrap().is_none());
+        assert!(matches!(
+            ProjectVersion::parse(VersionFileKind::Sdkmanrc, ".sdkmanrc", "java=21.0.2-nope"),
+            Err(Error::InvalidVersionFile { .. }),
+        ));
+    }
+
+    #[test]
+    fn find_project_version_test() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("a/b");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        std::fs::write(root.path().join(".java-version"), "21\n").unwrap();
+        std::fs::write(root.path().join("a/.tool-versions"), "nodejs 20\n").unwrap();
+
+        let found = ProjectVersion::find(&nested).unwrap().unwrap();
+        assert_eq!(found.kind, VersionFileKind::JavaVersion);
+        assert_eq!(found.path, root.path().join(".java-version"));
+
+        std::fs::write(root.path().join("a/.sdkmanrc"), "java=17.0.9-tem\n").unwrap();
+
+        let found = ProjectVersion::find(&nested).unwrap().unwrap();
+        assert_eq!(found.query_options().distribution, vec!["temurin"]);
+    }
+}
@@ -0,0 +1,379 @@
+use std::{cmp::Ordering, collections::{BTreeMap, HashMap}};
+
+use crate::{
+    http::{DiscoClient, PackageQueryOptions},
+    package::Package,
+    platform::Platform,
+    types::{ArchiveType, LibCType, OperatingSystem, PackageType, ReleaseStatus, Verification},
+    version::VersionReq,
+    Error,
+};
+
+/// A description of the package a caller wants, see [`Resolver`].
+#[derive(Debug, Clone)]
+pub struct JdkSpec {
+    pub version: VersionReq,
+    pub platform: Platform,
+    pub package_type: PackageType,
+    /// Distributions in order of preference. Other distributions are still
+    /// considered, ranked after these.
+    pub distributions: Vec<String>,
+    /// Accepted archive types per operating system, best first. Systems
+    /// without an entry use [`JdkSpec::default_archive_types`].
+    pub archive_types: HashMap<OperatingSystem, Vec<ArchiveType>>,
+    /// Whether JavaFX has to be bundled.
+    pub javafx: bool,
+}
+
+impl JdkSpec {
+    /// A JDK matching `version` for the current platform.
+    pub fn new(version: VersionReq) -> Self {
+        Self::for_platform(version, Platform::current())
+    }
+
+    /// A JDK matching `version` for `platform`, without detecting the
+    /// current one.
+    pub fn for_platform(version: VersionReq, platform: Platform) -> Self {
+        Self {
+            version,
+            platform,
+            package_type: PackageType::Jdk,
+            distributions: vec![],
+            archive_types: HashMap::new(),
+            javafx: false,
+        }
+    }
+
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    pub fn package_type(mut self, package_type: PackageType) -> Self {
+        self.package_type = package_type;
+        self
+    }
+
+    pub fn distributions(mut self, distributions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.distributions = distributions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn archive_types(mut self, operating_system: OperatingSystem, archive_types: Vec<ArchiveType>) -> Self {
+        self.archive_types.insert(operating_system, archive_types);
+        self
+    }
+
+    pub fn javafx(mut self, required: bool) -> Self {
+        self.javafx = required;
+        self
+    }
+
+    /// The archive types that can be unpacked without an installer: `zip` on
+    /// Windows, tarballs first elsewhere.
+    pub fn default_archive_types(operating_system: &OperatingSystem) -> Vec<ArchiveType> {
+        match operating_system {
+            OperatingSystem::Windows => vec![ArchiveType::Zip],
+            _ => vec![ArchiveType::TarGz, ArchiveType::Tgz, ArchiveType::Tar, ArchiveType::Zip],
+        }
+    }
+
+    /// The accepted archive types for the spec's platform, best first.
+    pub fn accepted_archive_types(&self) -> Vec<ArchiveType> {
+        self.archive_types
+            .get(&self.platform.operating_system)
+            .cloned()
+            .unwrap_or_else(|| Self::default_archive_types(&self.platform.operating_system))
+    }
+
+    /// Query options that fetch the candidates for this spec.
+    ///
+    /// Distributions aren't filtered, since they are only a preference.
+    pub fn query_options(&self) -> PackageQueryOptions {
+        let mut opts = PackageQueryOptions {
+            version: self.version.to_server_version(),
+            package_type: vec![self.package_type.clone()],
+            archive_type: self.accepted_archive_types(),
+            javafx_bundled: self.javafx.then_some(true),
+            ..Default::default()
+        };
+
+        self.platform.merge_into(&mut opts);
+        opts
+    }
+
+    /// Why `package` can't satisfy this spec, `None` if it can.
+    fn rejection(&self, package: &Package) -> Option<&'static str> {
+        let platform = &self.platform;
+
+        let os_matches = package.operating_system == platform.operating_system
+            || (platform.operating_system == OperatingSystem::Linux
+                && platform.libc_type == LibCType::Musl
+                && matches!(package.operating_system, OperatingSystem::AlpineLinux | OperatingSystem::LinuxMusl));
+
+        let libc_matches = platform.operating_system != OperatingSystem::Linux
+            || package.operating_system != OperatingSystem::Linux
+            || package.lib_c_type == platform.libc_type;
+
+        if !self.version.matches(&package.java_version) {
+            Some("version doesn't match")
+        } else if package.package_type != self.package_type {
+            Some("different package type")
+        } else if !os_matches {
+            Some("different operating system")
+        } else if package.architecture.normalized() != platform.architecture.normalized() {
+            Some("different architecture")
+        } else if !libc_matches {
+            Some("different C library")
+        } else if !self.accepted_archive_types().contains(&package.archive_type) {
+            Some("archive type not accepted")
+        } else if self.javafx && !package.javafx_bundled {
+            Some("JavaFX not bundled")
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for JdkSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} for {}-{}",
+            self.package_type,
+            self.version,
+            self.platform.operating_system,
+            self.platform.architecture,
+        )?;
+
+        if self.javafx {
+            f.write_str(" with JavaFX")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Orders the packages that satisfy a [`JdkSpec`].
+pub trait RankingPolicy {
+    /// Compares two candidates, `Greater` means `a` is the better choice.
+    fn compare(&self, spec: &JdkSpec, a: &Package, b: &Package) -> Ordering;
+
+    /// Why `winner` was chosen over `runner_up`.
+    fn explain(&self, _spec: &JdkSpec, _winner: &Package, runner_up: &Package) -> String {
+        format!("ranked above {} by the ranking policy", describe(runner_up))
+    }
+}
+
+type Criterion = (&'static str, fn(&JdkSpec, &Package, &Package) -> Ordering);
+
+/// The default ranking, by these criteria in order:
+///
+/// 1. position in [`JdkSpec::distributions`]
+/// 2. GA before early access
+/// 3. higher Java version
+/// 4. `latest_build_available`
+/// 5. TCK tested, then AQAvit certified
+/// 6. free use in production
+/// 7. position in the accepted archive types
+/// 8. without JavaFX, unless it's required
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRanking;
+
+impl DefaultRanking {
+    const CRITERIA: &'static [Criterion] = &[
+        ("preferred distribution", |spec, a, b| {
+            let rank = |p: &Package| {
+                spec.distributions
+                    .iter()
+                    .position(|d| d.eq_ignore_ascii_case(&p.distribution))
+                    .unwrap_or(usize::MAX)
+            };
+            rank(b).cmp(&rank(a))
+        }),
+        ("GA release", |_, a, b| {
+            (a.release_status == ReleaseStatus::Ga).cmp(&(b.release_status == ReleaseStatus::Ga))
+        }),
+        ("newer version", |_, a, b| a.java_version.cmp(&b.java_version)),
+        ("latest build", |_, a, b| a.latest_build_available.cmp(&b.latest_build_available)),
+        ("TCK tested", |_, a, b| {
+            (a.tck_tested == Verification::Yes).cmp(&(b.tck_tested == Verification::Yes))
+        }),
+        ("AQAvit certified", |_, a, b| {
+            (a.aqavit_certified == Verification::Yes).cmp(&(b.aqavit_certified == Verification::Yes))
+        }),
+        ("free use in production", |_, a, b| a.free_use_in_production.cmp(&b.free_use_in_production)),
+        ("preferred archive type", |spec, a, b| {
+            let types = spec.accepted_archive_types();
+            let rank = |p: &Package| types.iter().position(|t| *t == p.archive_type).unwrap_or(usize::MAX);
+            rank(b).cmp(&rank(a))
+        }),
+        ("JavaFX only when required", |spec, a, b| {
+            (a.javafx_bundled == spec.javafx).cmp(&(b.javafx_bundled == spec.javafx))
+        }),
+    ];
+}
+
+impl RankingPolicy for DefaultRanking {
+    fn compare(&self, spec: &JdkSpec, a: &Package, b: &Package) -> Ordering {
+        Self::CRITERIA
+            .iter()
+            .map(|(_, c)| c(spec, a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn explain(&self, spec: &JdkSpec, winner: &Package, runner_up: &Package) -> String {
+        match Self::CRITERIA.iter().find(|(_, c)| c(spec, winner, runner_up).is_ne()) {
+            Some((name, _)) => format!("ranked above {} by {}", describe(runner_up), name),
+            None => format!("ranked equal to {}, picked first", describe(runner_up)),
+        }
+    }
+}
+
+/// The package chosen for a spec, with the reasons for the choice.
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub package: Package,
+    /// How many packages satisfied the spec.
+    pub candidates: usize,
+    /// Human readable explanation, in order.
+    pub reasons: Vec<String>,
+}
+
+/// Picks the single best package for a [`JdkSpec`].
+///
+/// Packages that don't satisfy the spec are dropped, the rest are ordered by
+/// the [`RankingPolicy`], [`DefaultRanking`] unless given.
+#[derive(Debug, Clone, Default)]
+pub struct Resolver<P: RankingPolicy = DefaultRanking> {
+    policy: P,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<P: RankingPolicy> Resolver<P> {
+    pub fn with_policy(policy: P) -> Self {
+        Self { policy }
+    }
+
+    /// Resolves `spec` against already fetched packages, e.g. from a
+    /// [`CatalogSnapshot`](crate::CatalogSnapshot) queried with
+    /// [`JdkSpec::query_options`].
+    pub fn resolve<'a>(
+        &self,
+        spec: &JdkSpec,
+        packages: impl IntoIterator<Item = &'a Package>,
+    ) -> Result<Resolution, Error> {
+        let mut rejected = BTreeMap::<&str, usize>::new();
+        let mut candidates = vec![];
+
+        for p in packages {
+            match spec.rejection(p) {
+                Some(reason) => *rejected.entry(reason).or_default() += 1,
+                None => candidates.push(p),
+            }
+        }
+
+        let rejections = rejected
+            .iter()
+            .map(|(reason, n)| format!("{} rejected: {}", n, reason))
+            .collect::<Vec<_>>();
+
+        // Stable, so equally ranked packages keep the server's order
+        candidates.sort_by(|a, b| self.policy.compare(spec, b, a));
+
+        let Some(best) = candidates.first() else {
+            return Err(Error::NoMatchingPackage(format!("{} ({})", spec, rejections.join(", "))));
+        };
+
+        let mut reasons = vec![format!("{} of {} packages satisfy {}", candidates.len(), candidates.len() + rejected.values().sum::<usize>(), spec)];
+        reasons.extend(rejections);
+        reasons.push(format!("chose {}", describe(best)));
+
+        if let Some(runner_up) = candidates.get(1) {
+            reasons.push(self.policy.explain(spec, best, runner_up));
+        }
+
+        Ok(Resolution {
+            package: (*best).clone(),
+            candidates: candidates.len(),
+            reasons,
+        })
+    }
+
+    /// Fetches the candidates for `spec` and resolves it.
+    pub fn resolve_with(&self, client: &DiscoClient, spec: &JdkSpec) -> Result<Resolution, Error> {
+        let packages = client.pull_packages(Some(spec.query_options()))?;
+
+        self.resolve(spec, &packages.result)
+    }
+}
+
+fn describe(p: &Package) -> String {
+    format!("{} {} ({}, {})", p.distribution, p.java_version, p.archive_type, p.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(id: &str, distribution: &str, java_version: &str, archive_type: &str) -> Package {
+        crate::test_support::package(id, distribution, java_version, serde_json::json!({ "archive_type": archive_type }))
+    }
+
+    fn spec() -> JdkSpec {
+        JdkSpec::for_platform(
+            VersionReq::parse("21").unwrap(),
+            Platform::from_target_triple("x86_64-unknown-linux-gnu").unwrap(),
+        )
+    }
+
+    #[test]
+    fn resolve_default_ranking_test() {
+        let packages = [
+            package("a", "zulu", "21.0.2+13", "tar.gz"),
+            package("b", "temurin", "21.0.1+12", "tar.gz"),
+            package("c", "temurin", "21.0.2+13", "zip"),
+            package("d", "temurin", "21.0.2+13", "tar.gz"),
+            package("e", "temurin", "17.0.9+9", "tar.gz"),
+            package("f", "temurin", "21.0.2+13", "msi"),
+        ];
+
+        let resolution = Resolver::new()
+            .resolve(&spec().distributions(["temurin", "zulu"]), &packages)
+            .unwrap();
+
+        assert_eq!(resolution.package.id, "d");
+        assert_eq!(resolution.candidates, 4);
+        assert!(resolution.reasons.iter().any(|r| r == "1 rejected: version doesn't match"));
+        assert!(resolution.reasons.last().unwrap().ends_with("by preferred archive type"));
+
+        let error = Resolver::new().resolve(&spec().javafx(true), &packages).unwrap_err();
+        assert!(matches!(error, Error::NoMatchingPackage(_)));
+    }
+
+    #[test]
+    fn resolve_custom_policy_test() {
+        struct Smallest;
+
+        impl RankingPolicy for Smallest {
+            fn compare(&self, _: &JdkSpec, a: &Package, b: &Package) -> Ordering {
+                b.java_version.cmp(&a.java_version)
+            }
+        }
+
+        let packages = [
+            package("a", "zulu", "21.0.2+13", "tar.gz"),
+            package("b", "temurin", "21.0.1+12", "tar.gz"),
+        ];
+
+        let resolution = Resolver::with_policy(Smallest).resolve(&spec(), &packages).unwrap();
+
+        assert_eq!(resolution.package.id, "b");
+    }
+}
@@ -0,0 +1,193 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// An item of a response list that couldn't be decoded and was skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DecodeWarning {
+    /// Position in the `result` array.
+    pub index: usize,
+    /// The item's `id` (or `api_parameter`, `major_version`), if it has one.
+    pub id: Option<String>,
+    pub message: String,
+}
+
+/// Differences between a response and the structs of this crate, reported by
+/// the strict `check_schema` functions.
+///
+/// Decoding is lenient and never fails on these, the report is meant for
+/// contract tests against the live API.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SchemaReport {
+    /// Fields the server sent that this crate doesn't know, like `result[3].foo`.
+    pub unknown_fields: Vec<String>,
+    /// Fields this crate expects that were missing or `null`.
+    pub missing_fields: Vec<String>,
+    /// Items that failed to decode.
+    pub warnings: Vec<DecodeWarning>,
+}
+
+impl SchemaReport {
+    pub fn is_clean(&self) -> bool {
+        self.unknown_fields.is_empty() && self.missing_fields.is_empty() && self.warnings.is_empty()
+    }
+}
+
+impl std::fmt::Display for SchemaReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} unknown field(s), {} missing field(s), {} undecodable item(s)",
+            self.unknown_fields.len(),
+            self.missing_fields.len(),
+            self.warnings.len(),
+        )
+    }
+}
+
+/// Field names of a response struct, for strict schema checks.
+pub(crate) trait Schema {
+    const FIELDS: &'static [&'static str];
+
+    /// Nested lists of other schema structs, by field name.
+    fn check_nested(_field: &str, _value: &Value, _path: &str, _report: &mut SchemaReport) {}
+}
+
+/// The `{ "result": [...], "message": "..." }` envelope, with items left
+/// undecoded so one bad item doesn't fail the whole list.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawList {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub result: Vec<Value>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub message: String,
+}
+
+/// Deserializes `null` as the default value, for use with `deserialize_with`.
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// Removes the elements of each item's `field` list that don't decode as
+/// `T`, collecting them as warnings. For lists like `versions`, where one odd
+/// entry shouldn't drop the whole item.
+pub(crate) fn strip_invalid_elements<T: DeserializeOwned>(items: &mut [Value], field: &str) -> Vec<DecodeWarning> {
+    let mut warnings = vec![];
+
+    for (index, item) in items.iter_mut().enumerate() {
+        let id = item_id(item);
+
+        let Some(Value::Array(elements)) = item.get_mut(field) else {
+            continue;
+        };
+
+        let mut position = 0;
+
+        elements.retain(|e| {
+            position += 1;
+
+            // Nulls are left for `decode_items` to drop without a warning
+            e.is_null() || serde_json::from_value::<T>(e.clone()).map_err(|err| warnings.push(DecodeWarning {
+                index,
+                id: id.clone(),
+                message: format!("{}[{}]: {}", field, position - 1, err),
+            })).is_ok()
+        });
+    }
+
+    warnings
+}
+
+/// Decodes every item on its own, collecting failures as warnings.
+///
+/// `null` fields are removed first, so they fall back to their defaults.
+pub(crate) fn decode_items<T: DeserializeOwned>(items: Vec<Value>) -> (Vec<T>, Vec<DecodeWarning>) {
+    let mut decoded = vec![];
+    let mut warnings = vec![];
+
+    for (index, mut item) in items.into_iter().enumerate() {
+        strip_nulls(&mut item);
+
+        let id = item_id(&item);
+
+        match serde_json::from_value(item) {
+            Ok(i) => decoded.push(i),
+            Err(e) => warnings.push(DecodeWarning {
+                index,
+                id,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    (decoded, warnings)
+}
+
+/// Decodes the single item of an info endpoint's `result`, along with the
+/// message. For `#[serde(try_from = "RawList")]`.
+pub(crate) fn decode_single<T: DeserializeOwned>(raw: RawList) -> Result<(T, String), String> {
+    let (result, warnings) = decode_items::<T>(raw.result);
+
+    match (result.into_iter().next(), warnings.into_iter().next()) {
+        (Some(item), _) => Ok((item, raw.message)),
+        (None, Some(w)) => Err(w.message),
+        (None, None) => Err(format!("empty result: {}", raw.message)),
+    }
+}
+
+/// Compares the items of a response's `result` list against `T`'s fields.
+pub(crate) fn check_list<T: Schema>(json: &str) -> Result<SchemaReport, serde_json::Error> {
+    let raw: RawList = serde_json::from_str(json)?;
+    let mut report = SchemaReport::default();
+
+    for (i, item) in raw.result.iter().enumerate() {
+        check_item::<T>(item, &format!("result[{}]", i), &mut report);
+    }
+
+    Ok(report)
+}
+
+pub(crate) fn check_item<T: Schema>(item: &Value, path: &str, report: &mut SchemaReport) {
+    let Some(object) = item.as_object() else {
+        report.missing_fields.push(path.to_string());
+        return;
+    };
+
+    for (k, v) in object {
+        if T::FIELDS.contains(&k.as_str()) {
+            T::check_nested(k, v, &format!("{}.{}", path, k), report);
+        } else {
+            report.unknown_fields.push(format!("{}.{}", path, k));
+        }
+    }
+
+    for f in T::FIELDS {
+        if object.get(*f).is_none_or(Value::is_null) {
+            report.missing_fields.push(format!("{}.{}", path, f));
+        }
+    }
+}
+
+fn item_id(item: &Value) -> Option<String> {
+    ["id", "api_parameter", "major_version"]
+        .iter()
+        .find_map(|k| item.get(k))
+        .map(|v| v.as_str().map(String::from).unwrap_or_else(|| v.to_string()))
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => {
+            items.retain(|v| !v.is_null());
+            items.iter_mut().for_each(strip_nulls);
+        }
+        _ => {}
+    }
+}
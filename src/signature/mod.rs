@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::Error;
+
+/// Public keys to verify detached package signatures with.
+///
+/// Keys can be trusted for every distribution or only for the distribution
+/// with the given `Distribution::api_parameter`. No vendor keys are bundled,
+/// they have to be added by the caller.
+///
+/// Verification runs the GnuPG `gpg` binary against a throwaway home
+/// directory, so the user's own keyring is never consulted.
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    gpg_program: PathBuf,
+    keys: Vec<Vec<u8>>,
+    distribution_keys: HashMap<String, Vec<Vec<u8>>>,
+}
+
+impl Default for Keyring {
+    fn default() -> Self {
+        Self {
+            gpg_program: PathBuf::from("gpg"),
+            keys: vec![],
+            distribution_keys: HashMap::new(),
+        }
+    }
+}
+
+impl Keyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path of the `gpg` binary, defaults to `gpg` from `PATH`.
+    pub fn gpg_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.gpg_program = program.into();
+        self
+    }
+
+    /// Adds an armored or binary public key trusted for every distribution.
+    pub fn add_key(&mut self, key: impl Into<Vec<u8>>) {
+        self.keys.push(key.into());
+    }
+
+    /// Adds a public key only trusted for `distribution` (its `api_parameter`).
+    pub fn add_distribution_key(&mut self, distribution: impl Into<String>, key: impl Into<Vec<u8>>) {
+        self.distribution_keys
+            .entry(distribution.into())
+            .or_default()
+            .push(key.into());
+    }
+
+    pub fn add_key_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.add_key(std::fs::read(path).map_err(Error::Io)?);
+        Ok(())
+    }
+
+    pub fn add_distribution_key_file(&mut self, distribution: impl Into<String>, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.add_distribution_key(distribution, std::fs::read(path).map_err(Error::Io)?);
+        Ok(())
+    }
+
+    fn keys_for<'a>(&'a self, distribution: Option<&str>) -> impl Iterator<Item = &'a Vec<u8>> {
+        self.keys
+            .iter()
+            .chain(distribution.and_then(|d| self.distribution_keys.get(d)).into_iter().flatten())
+    }
+
+    /// Verifies `file` against a detached `signature` (`.sig` or `.asc`).
+    ///
+    /// Only keys trusted globally or for `distribution` are used. Fails
+    /// with [`Error::NoSignatureKeys`] if there are none.
+    pub fn verify(&self, file: impl AsRef<Path>, signature: &[u8], distribution: Option<&str>) -> Result<(), Error> {
+        let keys = self.keys_for(distribution).collect::<Vec<_>>();
+
+        if keys.is_empty() {
+            return Err(Error::NoSignatureKeys(distribution.unwrap_or("<any>").to_string()));
+        }
+
+        let home = TempHome::new()?;
+
+        let mut key_paths = vec![];
+
+        for (i, key) in keys.iter().enumerate() {
+            let key_path = home.path.join(format!("key-{}", i));
+            std::fs::write(&key_path, key).map_err(Error::Io)?;
+            key_paths.push(key_path);
+        }
+
+        let sig_path = home.path.join("signature");
+        std::fs::write(&sig_path, signature).map_err(Error::Io)?;
+
+        let import = self.gpg(&home.path)
+            .arg("--import")
+            .args(&key_paths)
+            .output()
+            .map_err(Error::Io)?;
+
+        if !import.status.success() {
+            return Err(Error::SignatureInvalid(String::from_utf8_lossy(&import.stderr).trim().to_string()));
+        }
+
+        let verify = self.gpg(&home.path)
+            .args(["--status-fd", "1", "--verify"])
+            .arg(&sig_path)
+            .arg(file.as_ref())
+            .output()
+            .map_err(Error::Io)?;
+
+        let status = String::from_utf8_lossy(&verify.stdout);
+
+        if verify.status.success() && status.lines().any(|l| l.starts_with("[GNUPG:] VALIDSIG")) {
+            Ok(())
+        } else {
+            Err(Error::SignatureInvalid(String::from_utf8_lossy(&verify.stderr).trim().to_string()))
+        }
+    }
+
+    fn gpg(&self, home: &Path) -> Command {
+        let mut cmd = Command::new(&self.gpg_program);
+        cmd.arg("--homedir").arg(home).args(["--batch", "--no-tty", "--quiet"]);
+        cmd
+    }
+}
+
+/// A private GnuPG home directory, removed on drop.
+struct TempHome {
+    path: PathBuf,
+}
+
+impl TempHome {
+    fn new() -> Result<Self, Error> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!("foojay-disco-gpg-{}-{}", std::process::id(), nanos));
+
+        std::fs::create_dir(&path).map_err(Error::Io)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700)).map_err(Error::Io)?;
+        }
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_for_test() {
+        let mut keyring = Keyring::new();
+        keyring.add_key("global");
+        keyring.add_distribution_key("zulu", "zulu");
+
+        let keys = |d| keyring.keys_for(d).map(|k| String::from_utf8_lossy(k).into_owned()).collect::<Vec<_>>();
+
+        assert_eq!(keys(Some("zulu")), vec!["global", "zulu"]);
+        assert_eq!(keys(Some("temurin")), vec!["global"]);
+        assert_eq!(keys(None), vec!["global"]);
+
+        let mut keyring = Keyring::new();
+        keyring.add_distribution_key("zulu", "zulu");
+
+        assert!(matches!(
+            keyring.verify("jdk.tar.gz", b"sig", Some("temurin")),
+            Err(Error::NoSignatureKeys(d)) if d == "temurin",
+        ));
+    }
+
+    /// Runs `verify` against a stub `gpg` that accepts imports and reports
+    /// `VALIDSIG` only for files containing `valid`.
+    #[cfg(unix)]
+    #[test]
+    fn verify_stub_gpg_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let gpg = dir.path().join("gpg");
+
+        std::fs::write(&gpg, concat!(
+            "#!/bin/sh\n",
+            "case \"$*\" in *--import*) exit 0 ;; esac\n",
+            "for file; do :; done\n",
+            "grep -q valid \"$file\" && echo \"[GNUPG:] VALIDSIG 0123456789ABCDEF\"\n",
+            "grep -q good \"$file\" && { echo \"[GNUPG:] GOODSIG 0123456789ABCDEF\"; exit 0; }\n",
+            "echo \"BAD signature\" >&2\n",
+            "exit 1\n",
+        )).unwrap();
+        std::fs::set_permissions(&gpg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut keyring = Keyring::new().gpg_program(&gpg);
+        keyring.add_key("key");
+
+        let verify = |content: &str| {
+            let file = dir.path().join("jdk.tar.gz");
+            std::fs::write(&file, content).unwrap();
+            keyring.verify(&file, b"sig", None)
+        };
+
+        assert!(verify("valid good").is_ok());
+        // A good signature without VALIDSIG, like from an expired key
+        assert!(matches!(verify("good"), Err(Error::SignatureInvalid(_))));
+        assert!(matches!(verify("tampered"), Err(Error::SignatureInvalid(m)) if m == "BAD signature"));
+    }
+}
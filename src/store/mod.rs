@@ -0,0 +1,451 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    discover::ReleaseInfo,
+    download::DownloadProgress,
+    extract,
+    http::{response, DiscoClient},
+    lock::LockEntry,
+    package::Package,
+    resolve::{JdkSpec, RankingPolicy, Resolver},
+    types::{Architecture, ArchiveType, LibCType, PackageType},
+    version::{JavaVersion, VersionReq},
+    Error,
+};
+
+const METADATA_FILE: &str = "install.json";
+const STAGING_DIR: &str = ".staging";
+const JDK_DIR: &str = "jdk";
+
+/// A directory of installed JDKs, one subdirectory per install.
+///
+/// Installs are downloaded, verified and extracted in a staging directory
+/// inside the store, then renamed into place. An interrupted install leaves
+/// at most a staging directory behind, never a half-extracted JDK.
+#[derive(Debug, Clone)]
+pub struct JdkStore {
+    dir: PathBuf,
+}
+
+/// An installed JDK.
+#[derive(Debug, Clone)]
+pub struct Installation {
+    /// Name of the install directory, like `temurin-21.0.2+13-x64-glibc-tar.gz`.
+    pub name: String,
+    pub dir: PathBuf,
+    /// The directory containing `bin/java`.
+    pub java_home: PathBuf,
+    pub package_id: String,
+    pub distribution: Option<String>,
+    pub java_version: Option<JavaVersion>,
+    pub installed_at: SystemTime,
+    /// The package as listed by the API, if it was installed from one.
+    pub package: Option<Package>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct InstallMetadata {
+    package_id: String,
+    distribution: Option<String>,
+    java_version: Option<JavaVersion>,
+    /// Relative to the install directory.
+    java_home: PathBuf,
+    installed_at: u64,
+    package: Option<Package>,
+}
+
+impl JdkStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// A store in the user data directory, `$XDG_DATA_HOME/foojay-disco/jdks`
+    /// on Linux.
+    pub fn in_user_data_dir() -> Result<Self, Error> {
+        let dir = crate::dirs::data_dir()
+            .ok_or_else(|| Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "no user data directory")))?;
+
+        Ok(Self::new(dir.join("jdks")))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Downloads, verifies and extracts `package`. Returns the existing
+    /// install if it's already there, fails with [`Error::InstallConflict`]
+    /// if its directory holds a different package.
+    pub fn install(
+        &self,
+        client: &DiscoClient,
+        package: &Package,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<Installation, Error> {
+        if let Some(i) = self.get(&package.id)? {
+            return Ok(i);
+        }
+
+        let name = install_name(
+            &package.distribution,
+            &package.java_version,
+            &package.package_type,
+            &package.architecture,
+            &package.lib_c_type,
+            &package.archive_type,
+            package.javafx_bundled,
+        );
+
+        let metadata = InstallMetadata {
+            package_id: package.id.clone(),
+            distribution: Some(package.distribution.clone()),
+            java_version: Some(package.java_version.clone()),
+            package: Some(package.clone()),
+            ..Default::default()
+        };
+
+        self.install_with(&name, &package.archive_type, metadata, |staging| {
+            let info = client.pull_package_info(package.id.clone())?;
+            let info = &info.result[0];
+
+            let path = client.download_package_info(info, staging, u64::try_from(package.size).ok().filter(|s| *s > 0), progress)?;
+            client.verify_package_file(&path, info)?;
+
+            Ok(path)
+        })
+    }
+
+    /// Same as [`install`](Self::install), by package id.
+    ///
+    /// The API doesn't list a package by id, so the install has no
+    /// [`Package`] and its version is read from the JDK's `release` file.
+    pub fn install_id(
+        &self,
+        client: &DiscoClient,
+        package: String,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<Installation, Error> {
+        if let Some(i) = self.get(&package)? {
+            return Ok(i);
+        }
+
+        let info = client.pull_package_info(package.clone())?;
+        let info = &info.result[0];
+
+        let archive_type = archive_type_from_filename(&info.filename)
+            .ok_or_else(|| Error::UnsupportedArchive(info.filename.clone()))?;
+
+        let metadata = InstallMetadata {
+            package_id: package.clone(),
+            ..Default::default()
+        };
+
+        self.install_with(&sanitize(&package), &archive_type, metadata, |staging| {
+            let path = client.download_package_info(info, staging, None, progress)?;
+            client.verify_package_file(&path, info)?;
+
+            Ok(path)
+        })
+    }
+
+    /// Resolves `spec` and installs the chosen package.
+    pub fn install_spec<P: RankingPolicy>(
+        &self,
+        client: &DiscoClient,
+        resolver: &Resolver<P>,
+        spec: &JdkSpec,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<Installation, Error> {
+        let resolution = resolver.resolve_with(client, spec)?;
+
+        self.install(client, &resolution.package, progress)
+    }
+
+    /// Installs a locked package, without resolving or asking the API. See
+    /// [`LockEntry::download`].
+    pub fn install_locked(
+        &self,
+        client: &DiscoClient,
+        entry: &LockEntry,
+        progress: impl FnMut(DownloadProgress),
+    ) -> Result<Installation, Error> {
+        if let Some(i) = self.get(&entry.package_id)? {
+            return Ok(i);
+        }
+
+        let name = install_name(
+            &entry.distribution,
+            &entry.java_version,
+            &entry.package_type,
+            &entry.platform.architecture,
+            &entry.platform.libc_type,
+            &entry.archive_type,
+            entry.javafx,
+        );
+
+        let metadata = InstallMetadata {
+            package_id: entry.package_id.clone(),
+            distribution: Some(entry.distribution.clone()),
+            java_version: Some(entry.java_version.clone()),
+            ..Default::default()
+        };
+
+        self.install_with(&name, &entry.archive_type, metadata, |staging| entry.download(client, staging, progress))
+    }
+
+    /// All installs, newest Java version first.
+    pub fn list(&self) -> Result<Vec<Installation>, Error> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(e) => e,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let mut installs = vec![];
+
+        for entry in entries {
+            let name = entry.map_err(Error::Io)?.file_name().to_string_lossy().into_owned();
+
+            if name.starts_with('.') {
+                continue;
+            }
+
+            if let Some(i) = self.read(&name)? {
+                installs.push(i);
+            }
+        }
+
+        installs.sort_by(|a, b| b.java_version.cmp(&a.java_version).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(installs)
+    }
+
+    /// The install of a package id.
+    pub fn get(&self, package_id: &str) -> Result<Option<Installation>, Error> {
+        Ok(self.list()?.into_iter().find(|i| i.package_id == package_id))
+    }
+
+    /// The newest install matching `req`.
+    pub fn find(&self, req: &VersionReq) -> Result<Option<Installation>, Error> {
+        Ok(self.list()?.into_iter().find(|i| i.java_version.as_ref().is_some_and(|v| req.matches(v))))
+    }
+
+    /// Removes an install by package id or name. Returns whether it existed.
+    ///
+    /// The install is moved out of the store first, so it disappears at once
+    /// even if deleting the files fails halfway.
+    pub fn uninstall(&self, id_or_name: &str) -> Result<bool, Error> {
+        let Some(install) = self.list()?.into_iter().find(|i| i.package_id == id_or_name || i.name == id_or_name) else {
+            return Ok(false);
+        };
+
+        let trash = self.staging_path(&install.name)?;
+
+        std::fs::rename(&install.dir, &trash).map_err(Error::Io)?;
+        std::fs::remove_dir_all(&trash).map_err(Error::Io)?;
+
+        Ok(true)
+    }
+
+    /// Removes leftovers of interrupted installs. Don't call this while
+    /// another process installs into the store.
+    pub fn purge_staging(&self) -> Result<(), Error> {
+        match std::fs::remove_dir_all(self.dir.join(STAGING_DIR)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::Io(e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fetches an archive into a staging directory with `fetch`, extracts it
+    /// next to it and renames the whole directory into place.
+    fn install_with(
+        &self,
+        name: &str,
+        archive_type: &ArchiveType,
+        mut metadata: InstallMetadata,
+        fetch: impl FnOnce(&Path) -> Result<PathBuf, Error>,
+    ) -> Result<Installation, Error> {
+        let package_id = metadata.package_id.clone();
+
+        // The name covers everything that tells packages apart, but two
+        // packages can still share one, like vendor re-uploads
+        let existing = |e: Option<Installation>| match e {
+            Some(i) if i.package_id != package_id => Err(Error::InstallConflict {
+                name: name.to_string(),
+                package_id: i.package_id,
+            }),
+            e => Ok(e),
+        };
+
+        if let Some(i) = existing(self.read(name)?)? {
+            return Ok(i);
+        }
+
+        let staging = self.staging_path(name)?;
+        std::fs::create_dir_all(&staging).map_err(Error::Io)?;
+
+        let result = (|| {
+            let archive = fetch(&staging)?;
+            let home = extract::extract_archive(&archive, archive_type, staging.join(JDK_DIR))?;
+            std::fs::remove_file(&archive).map_err(Error::Io)?;
+
+            metadata.java_home = home.strip_prefix(&staging).unwrap_or(&home).to_path_buf();
+            metadata.java_version = metadata.java_version.take().or_else(|| ReleaseInfo::read(&home).and_then(|r| r.java_version()));
+            metadata.installed_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+            let json = serde_json::to_vec_pretty(&metadata)
+                .map_err(|e| response::json_error(name, "", e))?;
+            std::fs::write(staging.join(METADATA_FILE), json).map_err(Error::Io)?;
+
+            std::fs::rename(&staging, self.dir.join(name)).map_err(Error::Io)
+        })();
+
+        if let Err(e) = result {
+            let _ = std::fs::remove_dir_all(&staging);
+
+            // Another process may have finished the same install first
+            return existing(self.read(name)?)?.ok_or(e);
+        }
+
+        self.read(name)?.ok_or_else(|| Error::JavaHomeNotFound(self.dir.join(name)))
+    }
+
+    fn read(&self, name: &str) -> Result<Option<Installation>, Error> {
+        let dir = self.dir.join(name);
+
+        let json = match std::fs::read_to_string(dir.join(METADATA_FILE)) {
+            Ok(j) => j,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::Io(e)),
+        };
+
+        let metadata: InstallMetadata = response::decode(&dir.join(METADATA_FILE).display().to_string(), &json)?;
+
+        Ok(Some(Installation {
+            name: name.to_string(),
+            java_home: dir.join(&metadata.java_home),
+            dir,
+            package_id: metadata.package_id,
+            distribution: metadata.distribution,
+            java_version: metadata.java_version,
+            installed_at: UNIX_EPOCH + Duration::from_secs(metadata.installed_at),
+            package: metadata.package,
+        }))
+    }
+
+    fn staging_path(&self, name: &str) -> Result<PathBuf, Error> {
+        let staging = self.dir.join(STAGING_DIR);
+        std::fs::create_dir_all(&staging).map_err(Error::Io)?;
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+
+        Ok(staging.join(format!("{}.{}.{}", name, std::process::id(), nanos)))
+    }
+}
+
+fn install_name(
+    distribution: &str,
+    java_version: &JavaVersion,
+    package_type: &PackageType,
+    architecture: &Architecture,
+    lib_c_type: &LibCType,
+    archive_type: &ArchiveType,
+    javafx: bool,
+) -> String {
+    let mut name = format!("{}-{}-{}-{}-{}", distribution, java_version, architecture.normalized(), lib_c_type, archive_type);
+
+    if *package_type != PackageType::Jdk {
+        name.push_str(&format!("-{}", package_type));
+    }
+
+    if javafx {
+        name.push_str("-fx");
+    }
+
+    sanitize(&name)
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "._+-".contains(c) { c } else { '_' })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+fn archive_type_from_filename(filename: &str) -> Option<ArchiveType> {
+    [ArchiveType::TarGz, ArchiveType::Tgz, ArchiveType::Tar, ArchiveType::Zip]
+        .into_iter()
+        .find(|t| filename.ends_with(&format!(".{}", t)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tar_gz;
+
+    fn install(store: &JdkStore, name: &str, id: &str, version: &str) -> Result<Installation, Error> {
+        let metadata = InstallMetadata {
+            package_id: id.to_string(),
+            ..Default::default()
+        };
+
+        let archive = tar_gz(&[
+            ("jdk/bin/java", b"java"),
+            ("jdk/release", format!("JAVA_VERSION=\"{}\"\n", version).as_bytes()),
+        ], &[]);
+
+        store.install_with(name, &ArchiveType::TarGz, metadata, |staging| {
+            let path = staging.join("jdk.tar.gz");
+            std::fs::write(&path, archive).map_err(Error::Io)?;
+            Ok(path)
+        })
+    }
+
+    #[test]
+    fn store_install_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JdkStore::new(dir.path());
+
+        let installed = install(&store, "a", "a", "17.0.9").unwrap();
+        assert_eq!(installed.java_home, dir.path().join("a/jdk"));
+        assert_eq!(installed.java_version, Some(JavaVersion::parse("17.0.9").unwrap()));
+
+        install(&store, "b", "b", "21.0.2").unwrap();
+
+        assert_eq!(install(&store, "a", "a", "17.0.9").unwrap().installed_at, installed.installed_at);
+        assert!(matches!(
+            install(&store, "a", "a-musl", "17.0.9"),
+            Err(Error::InstallConflict { package_id, .. }) if package_id == "a",
+        ));
+
+        let names = store.list().unwrap().into_iter().map(|i| i.name).collect::<Vec<_>>();
+        assert_eq!(names, vec!["b", "a"]);
+
+        let found = store.find(&VersionReq::parse("17").unwrap()).unwrap().unwrap();
+        assert_eq!(found.package_id, "a");
+
+        assert!(store.uninstall("a").unwrap());
+        assert!(!store.uninstall("a").unwrap());
+        assert!(store.get("a").unwrap().is_none());
+        assert!(!dir.path().join("a").exists());
+    }
+
+    #[test]
+    fn store_interrupted_install_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JdkStore::new(dir.path());
+
+        let result = store.install_with("a", &ArchiveType::TarGz, InstallMetadata::default(), |staging| {
+            std::fs::write(staging.join("partial"), b"...").map_err(Error::Io)?;
+            Err(Error::ChecksumMismatch { expected: "00".to_string(), actual: "11".to_string() })
+        });
+
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+        assert!(!dir.path().join("a").exists());
+        assert_eq!(std::fs::read_dir(dir.path().join(STAGING_DIR)).unwrap().count(), 0);
+    }
+}
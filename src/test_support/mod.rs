@@ -0,0 +1,59 @@
+//! Fixtures shared by the unit tests.
+
+use serde_json::Value;
+
+use crate::{package::Package, version::JavaVersion};
+
+/// The JSON of a GA Linux x64 glibc `tar.gz` JDK with every field set.
+/// `fields` overrides or adds fields.
+pub(crate) fn package_json(id: &str, distribution: &str, java_version: &str, fields: Value) -> Value {
+    let feature = JavaVersion::parse(java_version).unwrap().feature();
+
+    let mut package = serde_json::json!({
+        "id": id, "archive_type": "tar.gz", "distribution": distribution,
+        "major_version": feature, "java_version": java_version, "distribution_version": java_version,
+        "jdk_version": feature, "latest_build_available": true, "release_status": "ga",
+        "term_of_support": "lts", "operating_system": "linux", "lib_c_type": "glibc",
+        "architecture": "x64", "fpu": "unknown", "package_type": "jdk",
+        "javafx_bundled": false, "directly_downloadable": true, "filename": "jdk.tar.gz",
+        "links": {}, "free_use_in_production": true, "tck_tested": "yes", "tck_cert_uri": "",
+        "aqavit_certified": "no", "aqavit_cert_uri": "", "size": 1, "feature": [],
+    });
+
+    if let (Some(package), Some(fields)) = (package.as_object_mut(), fields.as_object()) {
+        package.extend(fields.clone());
+    }
+
+    package
+}
+
+/// [`package_json`], decoded.
+pub(crate) fn package(id: &str, distribution: &str, java_version: &str, fields: Value) -> Package {
+    serde_json::from_value(package_json(id, distribution, java_version, fields)).unwrap()
+}
+
+/// A `tar.gz` of `entries`, then `links` as `(type, path, target)`.
+///
+/// Entry names are written to the header as is, so tests can build archives
+/// with paths like `../evil` that the `tar` crate would refuse.
+pub(crate) fn tar_gz(entries: &[(&str, &[u8])], links: &[(tar::EntryType, &str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(vec![], flate2::Compression::fast()));
+
+    for (path, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append(&header, *data).unwrap();
+    }
+
+    for (entry_type, path, target) in links {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(*entry_type);
+        header.set_size(0);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+
+    builder.into_inner().unwrap().finish().unwrap()
+}
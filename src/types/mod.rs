@@ -0,0 +1,302 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Declares an enum for a Disco API string value.
+///
+/// Every enum gets an `Other(String)` variant, so values the server adds later
+/// still deserialize. Parsing ignores ASCII case, serializing always uses the
+/// exact API spelling.
+macro_rules! api_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $value:literal),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+            Other(String),
+        }
+
+        impl $name {
+            /// All known values, excluding `Other`.
+            pub const KNOWN: &'static [$name] = &[$($name::$variant),*];
+
+            /// The value as used by the Disco API.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)*
+                    $name::Other(s) => s,
+                }
+            }
+        }
+
+        /// An empty `Other`, used when the server omits the field.
+        impl Default for $name {
+            fn default() -> Self {
+                $name::Other(String::new())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                $(
+                    if s.eq_ignore_ascii_case($value) {
+                        return $name::$variant;
+                    }
+                )*
+
+                $name::Other(s.to_string())
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(s: String) -> Self {
+                $name::from(s.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name::from(s))
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name::from)
+            }
+        }
+    };
+}
+
+api_enum! {
+    pub enum Architecture {
+        Aarch64 => "aarch64",
+        Amd64 => "amd64",
+        Arm => "arm",
+        Arm64 => "arm64",
+        Mips => "mips",
+        Ppc => "ppc",
+        Ppc64 => "ppc64",
+        Ppc64el => "ppc64el",
+        Ppc64le => "ppc64le",
+        Riscv64 => "riscv64",
+        S390 => "s390",
+        S390x => "s390x",
+        Sparc => "sparc",
+        Sparcv9 => "sparcv9",
+        X64 => "x64",
+        X86_64 => "x86-64",
+        X86 => "x86",
+        X86_32 => "x86-32",
+        I386 => "i386",
+        I486 => "i486",
+        I586 => "i586",
+        I686 => "i686",
+    }
+}
+
+impl Architecture {
+    /// The canonical spelling of aliases, like `amd64` for `x64` or `arm64`
+    /// for `aarch64`. Other values are returned as is.
+    pub fn normalized(&self) -> Architecture {
+        match self {
+            Architecture::Amd64 | Architecture::X86_64 => Architecture::X64,
+            Architecture::Arm64 => Architecture::Aarch64,
+            Architecture::X86_32
+            | Architecture::I386
+            | Architecture::I486
+            | Architecture::I586
+            | Architecture::I686 => Architecture::X86,
+            Architecture::Ppc64el => Architecture::Ppc64le,
+            a => a.clone(),
+        }
+    }
+
+    /// Bitness of the architecture, `None` for unknown ones.
+    pub fn bitness(&self) -> Option<Bitness> {
+        match self {
+            Architecture::Aarch64
+            | Architecture::Amd64
+            | Architecture::Arm64
+            | Architecture::Ppc64
+            | Architecture::Ppc64el
+            | Architecture::Ppc64le
+            | Architecture::Riscv64
+            | Architecture::S390x
+            | Architecture::Sparcv9
+            | Architecture::X64
+            | Architecture::X86_64 => Some(Bitness::Bit64),
+            Architecture::Arm
+            | Architecture::Mips
+            | Architecture::Ppc
+            | Architecture::S390
+            | Architecture::Sparc
+            | Architecture::X86
+            | Architecture::X86_32
+            | Architecture::I386
+            | Architecture::I486
+            | Architecture::I586
+            | Architecture::I686 => Some(Bitness::Bit32),
+            Architecture::Other(_) => None,
+        }
+    }
+}
+
+api_enum! {
+    pub enum OperatingSystem {
+        Aix => "aix",
+        AlpineLinux => "alpine_linux",
+        Linux => "linux",
+        LinuxMusl => "linux_musl",
+        Macos => "macos",
+        Qnx => "qnx",
+        Solaris => "solaris",
+        Windows => "windows",
+    }
+}
+
+api_enum! {
+    pub enum ArchiveType {
+        Apk => "apk",
+        Cab => "cab",
+        Deb => "deb",
+        Dmg => "dmg",
+        Exe => "exe",
+        Msi => "msi",
+        Pkg => "pkg",
+        Rpm => "rpm",
+        SrcTarGz => "src.tar.gz",
+        Tar => "tar",
+        TarGz => "tar.gz",
+        TarZ => "tar.Z",
+        Tgz => "tgz",
+        Zip => "zip",
+    }
+}
+
+api_enum! {
+    pub enum PackageType {
+        Jdk => "jdk",
+        Jre => "jre",
+    }
+}
+
+api_enum! {
+    pub enum LibCType {
+        CStdLib => "c_std_lib",
+        Glibc => "glibc",
+        Libc => "libc",
+        Musl => "musl",
+    }
+}
+
+api_enum! {
+    pub enum ReleaseStatus {
+        Ga => "ga",
+        Ea => "ea",
+    }
+}
+
+api_enum! {
+    pub enum TermOfSupport {
+        Lts => "lts",
+        Mts => "mts",
+        Sts => "sts",
+    }
+}
+
+api_enum! {
+    pub enum Bitness {
+        Bit32 => "32",
+        Bit64 => "64",
+    }
+}
+
+api_enum! {
+    pub enum Fpu {
+        HardFloat => "hard_float",
+        SoftFloat => "soft_float",
+        Unknown => "unknown",
+    }
+}
+
+api_enum! {
+    /// Whether a package passed the TCK or AQAvit.
+    pub enum Verification {
+        Yes => "yes",
+        No => "no",
+        Unknown => "unknown",
+    }
+}
+
+api_enum! {
+    /// The `latest` filter of the packages endpoints. The wire values use
+    /// underscores, like `per_distro`, see https://api.foojay.io/swagger-ui.
+    pub enum Latest {
+        Available => "available",
+        PerDistro => "per_distro",
+        PerVersion => "per_version",
+        AllOfVersion => "all_of_version",
+    }
+}
+
+api_enum! {
+    /// The named queries of the `major_versions` endpoint.
+    pub enum MajorVersionQuery {
+        LatestGa => "latest_ga",
+        LatestEa => "latest_ea",
+        LatestLts => "latest_lts",
+        LatestSts => "latest_sts",
+        Useful => "useful",
+    }
+}
+
+api_enum! {
+    pub enum ChecksumType {
+        Md5 => "md5",
+        Sha1 => "sha1",
+        Sha256 => "sha256",
+        Sha512 => "sha512",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_enum_round_trip_test() {
+        for a in Architecture::KNOWN {
+            assert_eq!(&Architecture::from(a.as_str()), a);
+        }
+
+        assert_eq!(ArchiveType::from("tar.gz"), ArchiveType::TarGz);
+        assert_eq!(TermOfSupport::from("LTS"), TermOfSupport::Lts);
+        assert_eq!(Latest::PerDistro.to_string(), "per_distro");
+    }
+
+    #[test]
+    fn api_enum_other_test() {
+        let os: OperatingSystem = serde_json::from_str("\"haiku\"").unwrap();
+
+        assert_eq!(os, OperatingSystem::Other("haiku".to_string()));
+        assert_eq!(serde_json::to_string(&os).unwrap(), "\"haiku\"");
+    }
+}
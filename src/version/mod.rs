@@ -0,0 +1,300 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+
+use crate::Error;
+
+mod requirement;
+
+pub use requirement::VersionReq;
+
+/// A Java version as returned by the Disco API.
+///
+/// Understands the modern `$FEATURE.$INTERIM.$UPDATE.$PATCH[-$PRE][+$BUILD][-$OPT]`
+/// scheme (JEP 322) with any number of extra components (`11.0.21.0.1`), as
+/// well as the legacy `1.8.0_392-b08` and `8u392-b08` forms. Ordering compares
+/// the numeric components, then pre-releases before releases, then the build.
+/// Vendor suffixes are kept but don't take part in comparisons.
+#[derive(Debug, Clone)]
+pub struct JavaVersion {
+    raw: String,
+    components: Vec<u32>,
+    pre_release: Option<String>,
+    build: Option<u32>,
+    optional: Option<String>,
+}
+
+impl JavaVersion {
+    pub fn parse(version: &str) -> Result<Self, Error> {
+        let raw = version.trim();
+        let invalid = || Error::VersionParse(raw.to_string());
+
+        // Skip prefixes like "jdk-" some vendors put in front
+        let mut s = raw.trim_start_matches(|c: char| !c.is_ascii_digit());
+
+        if s.is_empty() {
+            return Err(invalid());
+        }
+
+        // Legacy "1.8.0_392", the feature version is the second component
+        if let Some(rest) = s.strip_prefix("1.") {
+            if rest.split(['.', '_', '-', '+']).next().and_then(|c| c.parse::<u32>().ok()).is_some_and(|c| c < 9) {
+                s = rest;
+            }
+        }
+
+        let (head, build_part) = match s.split_once('+') {
+            Some((h, b)) => (h, Some(b)),
+            None => (s, None),
+        };
+
+        let (numbers, suffix) = match head.split_once('-') {
+            Some((n, p)) => (n, Some(p)),
+            None => (head, None),
+        };
+
+        // Legacy "8u392"
+        let numbers = numbers.replacen('u', ".0.", 1);
+
+        let mut components = vec![];
+        let mut optional = None;
+        let mut build = None;
+
+        for (i, c) in numbers.split(['.', '_']).enumerate() {
+            match c.parse::<u32>() {
+                Ok(n) => components.push(n),
+                // "8u392b08" has the build glued to the update
+                Err(_) if c.contains('b') && i > 0 => {
+                    let (n, b) = c.split_once('b').unwrap();
+                    components.push(n.parse().map_err(|_| invalid())?);
+                    build = Some(b.parse().map_err(|_| invalid())?);
+                }
+                Err(_) if i > 0 => {
+                    optional = Some(numbers.split(['.', '_']).skip(i).collect::<Vec<_>>().join("."));
+                    break;
+                }
+                Err(_) => return Err(invalid()),
+            }
+        }
+
+        let mut pre_release = None;
+
+        if let Some(suffix) = suffix {
+            let lower = suffix.to_ascii_lowercase();
+
+            if let Some(b) = lower.strip_prefix('b').and_then(|b| b.parse::<u32>().ok()) {
+                build = Some(b);
+            } else if ["ea", "alpha", "beta", "rc"].iter().any(|p| lower.starts_with(p)) {
+                pre_release = Some(suffix.to_string());
+            } else {
+                optional = Some(suffix.to_string());
+            }
+        }
+
+        if let Some(b) = build_part {
+            let digits = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+
+            if digits > 0 {
+                build = Some(b[..digits].parse().map_err(|_| invalid())?);
+            }
+
+            let rest = b[digits..].trim_start_matches(['-', '.']);
+
+            if !rest.is_empty() {
+                optional = Some(rest.to_string());
+            }
+        }
+
+        Ok(Self {
+            raw: raw.to_string(),
+            components,
+            pre_release,
+            build,
+            optional,
+        })
+    }
+
+    /// A version made of numeric components only.
+    pub(crate) fn from_components(components: Vec<u32>) -> Self {
+        Self {
+            raw: components.iter().map(u32::to_string).collect::<Vec<_>>().join("."),
+            components,
+            pre_release: None,
+            build: None,
+            optional: None,
+        }
+    }
+
+    /// The version string as it was parsed.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// All numeric components, starting with the feature version.
+    pub fn components(&self) -> &[u32] {
+        &self.components
+    }
+
+    pub fn feature(&self) -> u32 {
+        self.component(0)
+    }
+
+    pub fn interim(&self) -> u32 {
+        self.component(1)
+    }
+
+    pub fn update(&self) -> u32 {
+        self.component(2)
+    }
+
+    pub fn patch(&self) -> u32 {
+        self.component(3)
+    }
+
+    pub fn build(&self) -> Option<u32> {
+        self.build
+    }
+
+    /// The pre-release identifier, like `ea`.
+    pub fn pre_release(&self) -> Option<&str> {
+        self.pre_release.as_deref()
+    }
+
+    /// Vendor specific suffix, like `LTS`.
+    pub fn optional(&self) -> Option<&str> {
+        self.optional.as_deref()
+    }
+
+    pub fn is_pre_release(&self) -> bool {
+        self.pre_release.is_some()
+    }
+
+    fn component(&self, i: usize) -> u32 {
+        self.components.get(i).copied().unwrap_or(0)
+    }
+
+    /// Components without trailing zeros, so "17" and "17.0.0" compare equal.
+    fn significant_components(&self) -> &[u32] {
+        let len = self.components.iter().rposition(|c| *c != 0).map_or(0, |i| i + 1);
+
+        &self.components[..len]
+    }
+}
+
+impl Ord for JavaVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.significant_components();
+        let b = other.significant_components();
+
+        (0..a.len().max(b.len()))
+            .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for JavaVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for JavaVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for JavaVersion {}
+
+impl std::hash::Hash for JavaVersion {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.significant_components().hash(state);
+        self.pre_release.hash(state);
+        self.build.hash(state);
+    }
+}
+
+impl std::str::FromStr for JavaVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for JavaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl Serialize for JavaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for JavaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> JavaVersion {
+        JavaVersion::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_java_version_test() {
+        let modern = v("17.0.9+9");
+        assert_eq!((modern.feature(), modern.interim(), modern.update(), modern.patch()), (17, 0, 9, 0));
+        assert_eq!(modern.build(), Some(9));
+
+        let ea = v("21-ea+35");
+        assert_eq!(ea.feature(), 21);
+        assert_eq!(ea.pre_release(), Some("ea"));
+        assert_eq!(ea.build(), Some(35));
+
+        let legacy = v("1.8.0_392-b08");
+        assert_eq!((legacy.feature(), legacy.update()), (8, 392));
+        assert_eq!(legacy.build(), Some(8));
+
+        let legacy_short = v("8u392b08");
+        assert_eq!((legacy_short.feature(), legacy_short.update()), (8, 392));
+        assert_eq!(legacy_short.build(), Some(8));
+
+        assert_eq!(v("11.0.21.0.1").components(), &[11, 0, 21, 0, 1]);
+
+        let vendor = v("17.0.9+9-LTS");
+        assert_eq!(vendor.build(), Some(9));
+        assert_eq!(vendor.optional(), Some("LTS"));
+        assert!(!vendor.is_pre_release());
+
+        assert!(JavaVersion::parse("latest").is_err());
+    }
+
+    #[test]
+    fn order_java_version_test() {
+        assert!(v("17.0.10") > v("17.0.9"));
+        assert!(v("21-ea+35") < v("21+35"));
+        assert!(v("21-ea+34") < v("21-ea+35"));
+        assert!(v("11.0.21.0.1") > v("11.0.21"));
+        assert!(v("1.8.0_392-b08") > v("1.8.0_382-b05"));
+        assert!(v("1.8.0_392") < v("11"));
+        assert_eq!(v("17"), v("17.0.0"));
+        assert_eq!(v("17.0.9+9"), v("17.0.9+9-LTS"));
+    }
+}
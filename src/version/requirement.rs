@@ -0,0 +1,243 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+use super::JavaVersion;
+
+/// A version requirement, like `>=17.0.8, <18`, `21.*` or `~11.0.20`.
+///
+/// Comparators are separated by commas and must all match. A partial version
+/// without an operator (`21`, `17.0.9`) matches every version starting with
+/// it, same as `=`. `~` allows updates within the given interim version (or
+/// feature version, if only that is given) and `^` within the feature version.
+///
+/// Pre-releases only match if one of the comparators names a pre-release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    raw: String,
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: JavaVersion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Prefix,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+impl VersionReq {
+    pub fn parse(req: &str) -> Result<Self, Error> {
+        let raw = req.trim();
+
+        let comparators = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty() && *c != "*")
+            .map(Comparator::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            raw: raw.to_string(),
+            comparators,
+        })
+    }
+
+    /// A requirement that matches every release.
+    pub fn any() -> Self {
+        Self {
+            raw: "*".to_string(),
+            comparators: vec![],
+        }
+    }
+
+    pub fn matches(&self, version: &JavaVersion) -> bool {
+        if version.is_pre_release() && !self.comparators.iter().any(|c| c.version.is_pre_release()) {
+            return false;
+        }
+
+        self.matches_any_release(version)
+    }
+
+    /// Like [`matches`](Self::matches), but pre-releases aren't excluded. This
+    /// is how the server treats its `version` parameter.
+    pub(crate) fn matches_any_release(&self, version: &JavaVersion) -> bool {
+        self.comparators.iter().all(|c| c.matches(version))
+    }
+
+    /// Parses a server side `version` parameter, as produced by
+    /// [`to_server_version`](Self::to_server_version).
+    pub(crate) fn from_server_version(version: &str) -> Result<Self, Error> {
+        match version.split_once("..<") {
+            Some((from, to)) => Self::parse(&format!(">={}, <{}", from, to)),
+            None => Self::parse(version),
+        }
+    }
+
+    /// The requirement as a value for the server side `version` parameter,
+    /// if the server can express it.
+    ///
+    /// Single versions are passed through, lower and upper bounds become the
+    /// `from..<to` range syntax. `None` means the requirement has to be
+    /// applied client side (or matches everything).
+    pub fn to_server_version(&self) -> Option<String> {
+        match self.comparators.as_slice() {
+            [c] if c.op == Op::Prefix => Some(c.version.to_string()),
+            [c] if c.op == Op::Tilde || c.op == Op::Caret => {
+                Some(format!("{}..<{}", c.version, c.upper_bound()?))
+            }
+            [a, b] => {
+                let (lower, upper) = if a.op == Op::GreaterEq { (a, b) } else { (b, a) };
+
+                (lower.op == Op::GreaterEq && upper.op == Op::Less)
+                    .then(|| format!("{}..<{}", lower.version, upper.version))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Comparator {
+    fn parse(s: &str) -> Result<Self, Error> {
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Prefix),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+            .iter()
+            .find_map(|(p, op)| s.strip_prefix(p).map(|r| (*op, r.trim())))
+            .unwrap_or((Op::Prefix, s));
+
+        let wildcard = rest.strip_suffix(".*").or_else(|| rest.strip_suffix(".x"));
+
+        if wildcard.is_some() && op != Op::Prefix {
+            return Err(Error::VersionParse(s.to_string()));
+        }
+
+        Ok(Self {
+            op,
+            version: JavaVersion::parse(wildcard.unwrap_or(rest))?,
+        })
+    }
+
+    fn matches(&self, v: &JavaVersion) -> bool {
+        match self.op {
+            Op::Prefix => self.is_prefix_of(v),
+            Op::Greater => *v > self.version && !self.is_prefix_of(v),
+            Op::GreaterEq => *v >= self.version,
+            Op::Less => *v < self.version && !self.is_prefix_of(v),
+            Op::LessEq => *v <= self.version || self.is_prefix_of(v),
+            Op::Tilde | Op::Caret => {
+                *v >= self.version && self.upper_bound().is_none_or(|u| *v < u)
+            }
+        }
+    }
+
+    /// Whether `v` starts with the components (and build, pre-release if given)
+    /// of this comparator.
+    fn is_prefix_of(&self, v: &JavaVersion) -> bool {
+        let components = self.version.components();
+
+        (0..components.len()).all(|i| v.components().get(i).copied().unwrap_or(0) == components[i])
+            && (self.version.build().is_none() || self.version.build() == v.build())
+            && (self.version.pre_release().is_none() || self.version.pre_release() == v.pre_release())
+    }
+
+    fn upper_bound(&self) -> Option<JavaVersion> {
+        let c = self.version.components();
+
+        let upper = match (self.op, c.len()) {
+            (Op::Tilde, n) if n >= 2 => vec![c[0], c[1] + 1],
+            (Op::Tilde | Op::Caret, _) => vec![c[0] + 1],
+            _ => return None,
+        };
+
+        Some(JavaVersion::from_components(upper))
+    }
+}
+
+impl std::str::FromStr for VersionReq {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl Serialize for VersionReq {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionReq {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(req: &str, version: &str) -> bool {
+        VersionReq::parse(req).unwrap().matches(&JavaVersion::parse(version).unwrap())
+    }
+
+    #[test]
+    fn version_req_matches_test() {
+        assert!(matches(">=17.0.8, <18", "17.0.9+9"));
+        assert!(matches(">=17.0.8, <18", "17.0.8+7"));
+        assert!(!matches(">=17.0.8, <18", "17.0.7+7"));
+        assert!(!matches(">=17.0.8, <18", "18+36"));
+
+        assert!(matches("21.*", "21.0.1+12"));
+        assert!(!matches("21.*", "22+36"));
+        assert!(matches("21", "21.0.1+12"));
+
+        assert!(matches("~11.0.20", "11.0.21"));
+        assert!(!matches("~11.0.20", "11.1.0"));
+        assert!(!matches("~11.0.20", "11.0.19"));
+        assert!(matches("^17.0.2", "17.0.9"));
+        assert!(!matches("^17.0.2", "18"));
+
+        assert!(matches("<=17.0", "17.0.9"));
+        assert!(!matches(">17.0", "17.0.9"));
+
+        assert!(!matches("21", "21-ea+35"));
+        assert!(matches("21-ea", "21-ea+35"));
+        assert!(matches("*", "8"));
+    }
+
+    #[test]
+    fn version_req_server_version_test() {
+        let server = |r: &str| VersionReq::parse(r).unwrap().to_server_version();
+
+        assert_eq!(server("21.*"), Some("21".to_string()));
+        assert_eq!(server("17.0.9+9"), Some("17.0.9+9".to_string()));
+        assert_eq!(server(">=17.0.8, <18"), Some("17.0.8..<18".to_string()));
+        assert_eq!(server("~11.0.20"), Some("11.0.20..<11.1".to_string()));
+        assert_eq!(server(">17"), None);
+    }
+}